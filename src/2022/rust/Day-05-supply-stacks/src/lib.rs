@@ -1,22 +1,167 @@
+use std::fmt;
+
 pub type Crate = Vec<char>;
 
 pub type Crates = Vec<Crate>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Move {
     pub from: usize,
     pub to: usize,
     pub how_many: usize,
 }
 
-pub fn move_crate(crates: &mut Crates, instruction: Move) {
-    let from = crates
-        .get_mut(instruction.from - 1)
-        .expect("No crate at from");
-    let slice = from.split_off(from.len() - instruction.how_many);
+#[derive(Debug, PartialEq)]
+pub enum MoveError {
+    /// The move references a stack id that does not exist (ids start at 1)
+    NoStack(usize),
+    /// The move asks for more crates than the stack holds
+    NotEnoughCrates {
+        stack: usize,
+        requested: usize,
+        available: usize,
+    },
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::NoStack(id) => write!(f, "No stack with id {}", id),
+            MoveError::NotEnoughCrates {
+                stack,
+                requested,
+                available,
+            } => write!(
+                f,
+                "Stack {} holds {} crates but {} were requested",
+                stack, available, requested
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// A crane model that knows how to carry crates from one stack to another
+pub trait Crane {
+    /// Reorders the crates lifted from the top of a stack before they are placed.
+    /// `lifted` is ordered bottom to top, as they were in the source stack
+    fn arrange(&self, lifted: &mut Crate);
+}
+
+/// Moves crates one at a time, so the lifted crates end up in reverse order
+pub struct CrateMover9000;
+
+/// Moves several crates at once, so the lifted crates keep their order
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9000 {
+    fn arrange(&self, lifted: &mut Crate) {
+        lifted.reverse();
+    }
+}
+
+impl Crane for CrateMover9001 {
+    fn arrange(&self, _lifted: &mut Crate) {}
+}
+
+/// Takes the crates that a move lifts off its source stack, ordered bottom to top.
+/// Nothing is removed if the move is invalid
+fn lift(crates: &mut Crates, instruction: &Move) -> Result<Crate, MoveError> {
+    let stack_index = |id: usize| match id {
+        0 => Err(MoveError::NoStack(id)),
+        id if id > crates.len() => Err(MoveError::NoStack(id)),
+        id => Ok(id - 1),
+    };
+    let from_index = stack_index(instruction.from)?;
+    stack_index(instruction.to)?;
+
+    let from = &mut crates[from_index];
+    if instruction.how_many > from.len() {
+        return Err(MoveError::NotEnoughCrates {
+            stack: instruction.from,
+            requested: instruction.how_many,
+            available: from.len(),
+        });
+    }
+
+    Ok(from.split_off(from.len() - instruction.how_many))
+}
+
+/// Applies a move with the given crane. The crates are left untouched if the move is invalid
+pub fn move_crate(
+    crane: &impl Crane,
+    crates: &mut Crates,
+    instruction: &Move,
+) -> Result<(), MoveError> {
+    let mut lifted = lift(crates, instruction)?;
+    crane.arrange(&mut lifted);
+
+    crates[instruction.to - 1].extend(lifted);
+    Ok(())
+}
+
+/// Replays moves one at a time over a set of stacks, remembering enough to undo them
+pub struct Replay<C: Crane> {
+    crane: C,
+    crates: Crates,
+    /// Every applied move with the crates it lifted, in their original order
+    history: Vec<(Move, Crate)>,
+}
+
+impl<C: Crane> Replay<C> {
+    pub fn new(crane: C, crates: Crates) -> Self {
+        Replay {
+            crane,
+            crates,
+            history: vec![],
+        }
+    }
+
+    /// The stacks as they are after the applied moves
+    pub fn crates(&self) -> &Crates {
+        &self.crates
+    }
+
+    /// The moves applied so far, oldest first
+    pub fn applied(&self) -> impl Iterator<Item = &Move> {
+        self.history.iter().map(|(m, _)| m)
+    }
+
+    /// Applies a move. Invalid moves leave the stacks and history untouched
+    pub fn apply(&mut self, instruction: &Move) -> Result<(), MoveError> {
+        let lifted = lift(&mut self.crates, instruction)?;
+
+        let mut arranged = lifted.clone();
+        self.crane.arrange(&mut arranged);
+        self.crates[instruction.to - 1].extend(arranged);
+
+        self.history.push((instruction.clone(), lifted));
+        Ok(())
+    }
+
+    /// Undoes the last applied move and returns it, or `None` if nothing was applied
+    pub fn revert(&mut self) -> Option<Move> {
+        let (instruction, lifted) = self.history.pop()?;
+
+        let to = &mut self.crates[instruction.to - 1];
+        to.truncate(to.len() - instruction.how_many);
+        self.crates[instruction.from - 1].extend(lifted);
+
+        Some(instruction)
+    }
+
+    pub fn into_crates(self) -> Crates {
+        self.crates
+    }
+}
 
-    let to = crates.get_mut(instruction.to - 1).expect("No crate at to");
-    to.extend(slice);
+/// Returns the crate on top of every stack, using a space for empty stacks
+pub fn top_crates(crates: &Crates) -> String {
+    crates
+        .iter()
+        .map(|c| c.last().unwrap_or(&' '))
+        .collect::<String>()
 }
 
 /// Takes in the input of a crates string and returns a vector of crates
@@ -25,7 +170,7 @@ pub fn parse_crates(input: &str) -> Crates {
 
     let id_line = lines.last().expect("No id lines found");
 
-    let total_crates = id_line.split_whitespace().collect::<String>().len();
+    let total_crates = id_line.split_whitespace().count();
 
     let mut crates: Crates = vec![];
 
@@ -50,6 +195,34 @@ pub fn parse_crates(input: &str) -> Crates {
     crates
 }
 
+/// Renders the stacks back to the puzzle diagram, id line included.
+/// The output has no trailing newline, so `parse_crates` reads it back unchanged
+pub fn render_crates(crates: &Crates) -> String {
+    let height = crates.iter().map(|c| c.len()).max().unwrap_or(0);
+
+    let mut lines: Vec<String> = (0..height)
+        .rev()
+        .map(|level| {
+            crates
+                .iter()
+                .map(|c| match c.get(level) {
+                    Some(cell) => format!("[{}]", cell),
+                    None => "   ".to_string(),
+                })
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect();
+
+    let id_line = (1..=crates.len())
+        .map(|id| format!("{:^3}", id))
+        .collect::<Vec<String>>()
+        .join(" ");
+    lines.push(id_line);
+
+    lines.join("\n")
+}
+
 pub fn parse_movements(input: &str) -> Vec<Move> {
     let mut moves: Vec<Move> = vec![];
 
@@ -75,8 +248,7 @@ mod tests {
 
     use super::*;
 
-    #[test]
-    fn test_example() {
+    fn run_mock(crane: &impl Crane) -> String {
         let input =
             fs::read_to_string("input_mock.txt").expect("Something went wrong reading the file");
 
@@ -89,18 +261,87 @@ mod tests {
         let moves = parse_movements(moves);
 
         for m in moves {
-            println!("Before\n {:?}", crates);
-            println!("Move {:?}", m);
-            move_crate(&mut crates, m);
-            println!("After\n {:?}", crates);
+            move_crate(crane, &mut crates, &m).expect("Mock moves should be valid");
         }
 
-        let final_input = crates
-            .iter()
-            .map(|c| c.last().unwrap_or(&' '))
-            .collect::<String>();
+        top_crates(&crates)
+    }
+
+    #[test]
+    fn test_example() {
+        assert_eq!(run_mock(&CrateMover9000), "CMZ");
+    }
+
+    #[test]
+    fn test_example_crate_mover_9001() {
+        assert_eq!(run_mock(&CrateMover9001), "MCD");
+    }
+
+    #[test]
+    fn test_invalid_moves() {
+        let mut crates: Crates = vec![vec!['Z', 'N'], vec!['M']];
+
+        let too_many = Move {
+            from: 2,
+            to: 1,
+            how_many: 2,
+        };
+        assert_eq!(
+            move_crate(&CrateMover9000, &mut crates, &too_many),
+            Err(MoveError::NotEnoughCrates {
+                stack: 2,
+                requested: 2,
+                available: 1
+            })
+        );
+
+        let no_stack = Move {
+            from: 1,
+            to: 3,
+            how_many: 1,
+        };
+        assert_eq!(
+            move_crate(&CrateMover9001, &mut crates, &no_stack),
+            Err(MoveError::NoStack(3))
+        );
+
+        assert_eq!(crates, vec![vec!['Z', 'N'], vec!['M']]);
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        for filename in ["input_mock.txt", "input.txt"] {
+            let input =
+                fs::read_to_string(filename).expect("Something went wrong reading the file");
+            let diagram = input.split("\n\n").next().expect("No crates found");
+
+            assert_eq!(render_crates(&parse_crates(diagram)), diagram);
+        }
+    }
+
+    #[test]
+    fn test_replay_apply_and_revert() {
+        let input =
+            fs::read_to_string("input_mock.txt").expect("Something went wrong reading the file");
+        let mut groups = input.split("\n\n");
+        let diagram = groups.next().expect("No crates found");
+        let moves = parse_movements(groups.next().expect("No moves found"));
+
+        let mut replay = Replay::new(CrateMover9000, parse_crates(diagram));
+        for m in &moves {
+            replay.apply(m).expect("Mock moves should be valid");
+        }
+        assert_eq!(top_crates(replay.crates()), "CMZ");
+        assert_eq!(replay.applied().count(), moves.len());
+
+        assert_eq!(replay.revert(), moves.last().cloned());
+        assert_eq!(
+            render_crates(replay.crates()),
+            "        [Z]\n        [N]\n[M]     [D]\n[C]     [P]\n 1   2   3 "
+        );
 
-        assert_eq!(final_input, "CMZ");
+        while replay.revert().is_some() {}
+        assert_eq!(render_crates(replay.crates()), diagram);
     }
 
     #[test]
@@ -112,7 +353,7 @@ move 8 from 5 to 6
 move 2 from 4 to 5
 move 1 from 4 to 5
 ";
-        let movements = parse_movements(&crates_raw);
+        let movements = parse_movements(crates_raw);
         println!("{:?}", movements);
     }
 
@@ -131,7 +372,7 @@ move 1 from 4 to 5
  1   2   3   4   5   6   7   8   9 
 ";
 
-        let crates = parse_crates(&crates_raw);
+        let crates = parse_crates(crates_raw);
         println!("{:?}", crates);
     }
 }
@@ -1,6 +1,17 @@
 use std::fs;
 
-use supply_stacks::{move_crate, parse_crates, parse_movements};
+use supply_stacks::{
+    move_crate, parse_crates, parse_movements, top_crates, Crane, CrateMover9000, CrateMover9001,
+    Crates, Move,
+};
+
+fn run(crane: &impl Crane, mut crates: Crates, moves: &[Move]) -> String {
+    for m in moves {
+        move_crate(crane, &mut crates, m).expect("Invalid move in input");
+    }
+
+    top_crates(&crates)
+}
 
 fn main() {
     let input = fs::read_to_string("input.txt").expect("Something went wrong reading the file");
@@ -10,14 +21,12 @@ fn main() {
     let crates = groups.next().expect("No crates found");
     let moves = groups.next().expect("No moves found");
 
-    let mut crates = parse_crates(crates);
+    let crates = parse_crates(crates);
     let moves = parse_movements(moves);
 
-    for m in moves {
-        move_crate(&mut crates, m);
-    }
-
-    for c in crates {
-        print!("{}", c.last().unwrap_or(&' '));
-    }
+    println!(
+        "CrateMover 9000: {}",
+        run(&CrateMover9000, crates.clone(), &moves)
+    );
+    println!("CrateMover 9001: {}", run(&CrateMover9001, crates, &moves));
 }
@@ -1,25 +1,82 @@
-use std::collections::HashSet;
+use std::io::{self, BufReader, Read};
+
+/// Sliding-window detector that finds the positions where the last `size`
+/// characters are all different. Works one byte at a time in O(1), keeping the
+/// last position where every byte was seen.
+pub struct MarkerDetector {
+    size: usize,
+    /// 1-based position of the last time each byte was seen, 0 if never
+    last_seen: [usize; 256],
+    /// 1-based position where the current run of distinct bytes starts
+    run_start: usize,
+    /// Number of bytes pushed so far
+    position: usize,
+}
 
-pub fn detect_sub_routine(signal: &str, diff_char_count: usize) -> Option<u32> {
-    for window in signal
-        .chars()
-        .enumerate()
-        .collect::<Vec<_>>()
-        .windows(diff_char_count)
-    {
-        let place = window[diff_char_count - 1].0 + 1;
-        let chars: HashSet<char> = window.iter().map(|x| x.1).collect();
-
-        if chars.len() == diff_char_count {
-            return Some(place as u32);
+impl MarkerDetector {
+    pub fn new(size: usize) -> Self {
+        MarkerDetector {
+            size,
+            last_seen: [0; 256],
+            run_start: 1,
+            position: 0,
+        }
+    }
+
+    /// Feeds the next byte of the signal. Returns its 1-based position if it
+    /// completes a marker
+    pub fn push(&mut self, byte: u8) -> Option<usize> {
+        self.position += 1;
+
+        let seen = &mut self.last_seen[byte as usize];
+        self.run_start = self.run_start.max(*seen + 1);
+        *seen = self.position;
+
+        if self.position + 1 - self.run_start >= self.size {
+            Some(self.position)
+        } else {
+            None
+        }
+    }
+}
+
+/// Streams the bytes of the signal from `reader`, buffered. Line breaks are
+/// not part of the signal and are skipped
+pub fn signal_bytes<R: Read>(reader: R) -> impl Iterator<Item = io::Result<u8>> {
+    BufReader::new(reader)
+        .bytes()
+        .filter(|byte| !matches!(byte, Ok(b'\n' | b'\r')))
+}
+
+/// Reads the whole signal from `reader` and returns every marker position of
+/// the given size
+pub fn find_markers<R: Read>(reader: R, size: usize) -> io::Result<Vec<usize>> {
+    let mut detector = MarkerDetector::new(size);
+    let mut markers = vec![];
+
+    for byte in signal_bytes(reader) {
+        if let Some(place) = detector.push(byte?) {
+            markers.push(place);
         }
     }
-    None
+
+    Ok(markers)
+}
+
+pub fn detect_sub_routine(signal: &str, diff_char_count: usize) -> Option<u32> {
+    let mut detector = MarkerDetector::new(diff_char_count);
+
+    signal
+        .trim_end()
+        .bytes()
+        .find_map(|byte| detector.push(byte))
+        .map(|place| place as u32)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
     use utility_2022::{get_input, is_demo_mode};
 
     mod part_1 {
@@ -66,12 +123,58 @@ mod tests {
 
             for t in trials.iter() {
                 let result =
-                    detect_sub_routine(&t.0, 4).expect("Test case with input must return a number");
+                    detect_sub_routine(t.0, 4).expect("Test case with input must return a number");
                 assert_eq!(result, t.1);
             }
         }
     }
 
+    mod markers {
+        use super::*;
+
+        fn naive_markers(signal: &str, size: usize) -> Vec<usize> {
+            let chars = signal.chars().collect::<Vec<_>>();
+            chars
+                .windows(size)
+                .enumerate()
+                .filter(|(_, w)| w.iter().collect::<HashSet<_>>().len() == size)
+                .map(|(i, _)| i + size)
+                .collect()
+        }
+
+        #[test]
+        fn test_all_markers() {
+            let signals = [
+                "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+                "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+                "aaaa",
+                "abcde",
+            ];
+
+            for signal in signals {
+                for size in [1, 4, 14] {
+                    let markers = find_markers(signal.as_bytes(), size).unwrap();
+                    assert_eq!(
+                        markers,
+                        naive_markers(signal, size),
+                        "Failed for {}",
+                        signal
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn test_long_stream() {
+            let signal = "abcdefghijklmnopqrstuvwxyz".repeat(100_000) + "\n";
+
+            let markers = find_markers(signal.as_bytes(), 14).unwrap();
+
+            assert_eq!(markers.len(), 26 * 100_000 - 13);
+            assert_eq!(markers.first(), Some(&14));
+        }
+    }
+
     mod part_2 {
 
         use super::*;
@@ -87,8 +190,8 @@ mod tests {
             ];
 
             for t in trials.iter() {
-                let result = detect_sub_routine(&t.0, 14)
-                    .expect("Test case with input must return a number");
+                let result =
+                    detect_sub_routine(t.0, 14).expect("Test case with input must return a number");
                 assert_eq!(result, t.1, "Failed for {}", t.0);
             }
         }
@@ -1,7 +1,49 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, Read},
+};
+
+use day_06_tuning_trouble::{signal_bytes, MarkerDetector};
 use utility_2022::get_input;
 
-fn main() {
-    let input = get_input();
+const START_OF_PACKET: usize = 4;
+const START_OF_MESSAGE: usize = 14;
+
+/// Usage: `day-06-tuning-trouble [FILE]`
+///
+/// Streams the signal from FILE (`-` for stdin) and prints the first
+/// start-of-packet and start-of-message markers, stopping as soon as both are
+/// found. Without FILE it reads the puzzle input, honouring DEMO_MODE
+fn main() -> io::Result<()> {
+    let signal: Box<dyn Read> = match env::args().nth(1).as_deref() {
+        Some("-") => Box::new(io::stdin()),
+        Some(path) => Box::new(File::open(path)?),
+        None => Box::new(io::Cursor::new(get_input())),
+    };
+
+    let mut packet = MarkerDetector::new(START_OF_PACKET);
+    let mut message = MarkerDetector::new(START_OF_MESSAGE);
+    let (mut packet_marker, mut message_marker) = (None, None);
+
+    for byte in signal_bytes(signal) {
+        let byte = byte?;
+        packet_marker = packet_marker.or(packet.push(byte));
+        message_marker = message_marker.or(message.push(byte));
+
+        if packet_marker.is_some() && message_marker.is_some() {
+            break;
+        }
+    }
+
+    match packet_marker {
+        Some(place) => println!("Start-of-packet marker: {}", place),
+        None => println!("No start-of-packet marker found"),
+    }
+    match message_marker {
+        Some(place) => println!("Start-of-message marker: {}", place),
+        None => println!("No start-of-message marker found"),
+    }
 
-    // println!("{}", input)
+    Ok(())
 }
@@ -1,6 +1,13 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{collections::HashMap, fmt};
 
-pub type NodeHanlder<T> = Rc<RefCell<T>>;
+mod report;
+mod transcript;
+
+pub use report::Node;
+pub use transcript::{disk_usage, generate_transcript, TranscriptOptions, Traversal};
+
+/// Index of a folder inside the [`FileSystem`] arena
+pub type FolderId = usize;
 
 /// File representation
 #[derive(Debug)]
@@ -9,43 +16,73 @@ pub struct File {
     pub name: String,
 }
 
-/// Folder representation
+/// An entry of a folder, in the order it was first listed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Entry {
+    Folder(FolderId),
+    /// Index into [`Folder::files`]
+    File(usize),
+}
+
+/// Folder representation. Folders live in the [`FileSystem`] arena and refer
+/// to each other by id
 #[derive(Debug)]
 pub struct Folder {
     name: String,
-    children: Vec<NodeHanlder<Folder>>,
+    parent: Option<FolderId>,
+    children: Vec<FolderId>,
+    children_by_name: HashMap<String, FolderId>,
     files: Vec<File>,
-    parent: Option<NodeHanlder<Folder>>,
+    files_by_name: HashMap<String, usize>,
+    entries: Vec<Entry>,
+    /// Cumulative size of the folder, including every subfolder
+    size: u64,
 }
 
 impl Folder {
-    pub fn new_rc(name: String, parent: Option<NodeHanlder<Folder>>) -> NodeHanlder<Folder> {
-        Rc::new(RefCell::new(Folder {
+    fn new(name: String, parent: Option<FolderId>) -> Folder {
+        Folder {
             name,
+            parent,
             children: vec![],
+            children_by_name: HashMap::new(),
             files: vec![],
-            parent,
-        }))
+            files_by_name: HashMap::new(),
+            entries: vec![],
+            size: 0,
+        }
     }
 
-    pub fn size(&self) -> u64 {
-        let mut size = 0;
-        for child in &self.children {
-            let folder = child.borrow();
-            size += folder.size();
-        }
-        for file in &self.files {
-            size += file.size;
-        }
-        size
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parent(&self) -> Option<FolderId> {
+        self.parent
     }
 
-    pub fn add_file(&mut self, file: File) {
-        self.files.push(file);
+    pub fn children(&self) -> &[FolderId] {
+        &self.children
     }
 
-    pub fn add_folder(&mut self, folder: NodeHanlder<Folder>) {
-        self.children.push(Rc::clone(&folder));
+    pub fn files(&self) -> &[File] {
+        &self.files
+    }
+
+    /// Subfolders and files, in the order they were first listed
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Size of the folder and everything below it. Computed once when the
+    /// file system is built
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Finds a direct subfolder by name
+    pub fn child(&self, name: &str) -> Option<FolderId> {
+        self.children_by_name.get(name).copied()
     }
 }
 
@@ -53,7 +90,7 @@ impl Folder {
 #[derive(Debug)]
 enum Command {
     Ls,
-    CdBack,
+    /// `cd` with a relative or absolute path, e.g. `a`, `..`, `/` or `/a/e`
     Cd(String),
 }
 
@@ -65,155 +102,292 @@ enum ParseResult {
     Folder(String),
 }
 
-/// File system representation
+/// Reason why a line of the transcript could not be used
+#[derive(Debug, PartialEq)]
+pub enum ParseErrorKind {
+    UnknownCommand(String),
+    MissingArgument,
+    InvalidSize(String),
+    UnknownDirectory(String),
+    AboveRoot,
+}
+
+/// Error found while reading a terminal transcript, with its 1-based line number
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ParseErrorKind::UnknownCommand(c) => write!(f, "unknown command `{}`", c),
+            ParseErrorKind::MissingArgument => write!(f, "missing argument"),
+            ParseErrorKind::InvalidSize(s) => write!(f, "cannot parse size `{}`", s),
+            ParseErrorKind::UnknownDirectory(d) => write!(f, "no directory `{}`", d),
+            ParseErrorKind::AboveRoot => write!(f, "cannot go above /"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// File system representation. Folders are stored in an arena where every
+/// folder comes after its parent, so sizes can be summed in a single pass
 #[derive(Debug)]
 pub struct FileSystem {
-    pub root: NodeHanlder<Folder>,
+    folders: Vec<Folder>,
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-pub fn find_sum_at_most(size: u64, folder: &NodeHanlder<Folder>) -> u64 {
-    let mut sum = 0;
+impl FileSystem {
+    pub const ROOT: FolderId = 0;
 
-    // Check size of current folder
+    /// Creates a file system with an empty root folder
+    pub fn new() -> FileSystem {
+        FileSystem {
+            folders: vec![Folder::new("/".into(), None)],
+        }
+    }
 
-    let f_size = folder.borrow().size();
+    pub fn folder(&self, id: FolderId) -> &Folder {
+        &self.folders[id]
+    }
 
-    if f_size <= size {
-        sum += f_size;
+    pub fn root(&self) -> &Folder {
+        self.folder(Self::ROOT)
     }
 
-    // Check for repeated folders
-    for child in &folder.borrow().children {
-        sum += find_sum_at_most(size, child);
+    /// Total size of the file system
+    pub fn size(&self) -> u64 {
+        self.root().size()
     }
-    sum
-}
 
-/// Finds the directories that can be deleted to free up the desired size
-/// Returns the smallest size that can be freed (if any)
-pub fn find_smallest_to_free(min_required_size: u64, folder: &NodeHanlder<Folder>) -> Option<u64> {
-    let size = folder.borrow().size();
+    /// All folders, in arena order
+    pub fn folders(&self) -> impl Iterator<Item = (FolderId, &Folder)> {
+        self.folders.iter().enumerate()
+    }
+
+    /// Adds a file, replacing the size of a file listed before with the same name
+    fn add_file(&mut self, folder: FolderId, file: File) {
+        let folder = &mut self.folders[folder];
+        match folder.files_by_name.get(&file.name) {
+            Some(&index) => folder.files[index].size = file.size,
+            None => {
+                let index = folder.files.len();
+                folder.files_by_name.insert(file.name.clone(), index);
+                folder.files.push(file);
+                folder.entries.push(Entry::File(index));
+            }
+        }
+    }
+
+    /// Adds a folder unless a folder with the same name is already there
+    fn add_folder(&mut self, parent: FolderId, name: String) -> FolderId {
+        if let Some(id) = self.folders[parent].child(&name) {
+            return id;
+        }
+
+        let id = self.folders.len();
+        self.folders.push(Folder::new(name.clone(), Some(parent)));
+
+        let parent = &mut self.folders[parent];
+        parent.children.push(id);
+        parent.children_by_name.insert(name, id);
+        parent.entries.push(Entry::Folder(id));
+        id
+    }
+
+    /// Fills in the cumulative folder sizes. Children always have a bigger id
+    /// than their parent, so walking the arena backwards visits them first
+    fn compute_sizes(&mut self) {
+        for folder in self.folders.iter_mut() {
+            folder.size = folder.files.iter().map(|f| f.size).sum();
+        }
+
+        for id in (1..self.folders.len()).rev() {
+            let size = self.folders[id].size;
+            if let Some(parent) = self.folders[id].parent {
+                self.folders[parent].size += size;
+            }
+        }
+    }
+
+    /// Finds the folder at a path, relative to `from` unless it starts with `/`
+    fn walk(&self, from: FolderId, path: &str) -> Result<FolderId, ParseErrorKind> {
+        let mut current = if path.starts_with('/') {
+            Self::ROOT
+        } else {
+            from
+        };
+
+        for part in path.split('/').filter(|p| !p.is_empty() && *p != ".") {
+            let folder = self.folder(current);
+            current = if part == ".." {
+                folder.parent.ok_or(ParseErrorKind::AboveRoot)?
+            } else {
+                folder
+                    .child(part)
+                    .ok_or_else(|| ParseErrorKind::UnknownDirectory(part.to_string()))?
+            };
+        }
+
+        Ok(current)
+    }
+
+    /// Looks up a folder by its absolute path, e.g. `/a/e`
+    pub fn resolve(&self, path: &str) -> Option<FolderId> {
+        self.walk(Self::ROOT, path).ok()
+    }
+
+    /// Absolute path of a folder, e.g. `/a/e`
+    pub fn path(&self, id: FolderId) -> String {
+        let mut parts = vec![];
+        let mut current = id;
+        while let Some(parent) = self.folder(current).parent {
+            parts.push(self.folder(current).name());
+            current = parent;
+        }
 
-    // If folder size is less than the required size, return None
-    if size < min_required_size {
-        return None;
+        parts.reverse();
+        format!("/{}", parts.join("/"))
     }
 
-    // Set default min_size
-    let min_size = size;
+    /// Every folder, parents before their children; use [`FileSystem::path`]
+    /// for the ones whose path is needed
+    pub fn directories(&self) -> Vec<FolderId> {
+        let mut directories = vec![];
+        let mut pending = vec![Self::ROOT];
 
-    let min_child = folder
-        .borrow()
-        .children
-        .iter()
-        .filter_map(|c| find_smallest_to_free(min_required_size, c))
-        .min();
+        while let Some(id) = pending.pop() {
+            pending.extend(self.folder(id).children.iter().rev());
+            directories.push(id);
+        }
+
+        directories
+    }
 
-    if min_child.is_some() {
-        return min_child;
+    /// Folders whose size is at most `size`
+    pub fn directories_at_most(&self, size: u64) -> impl Iterator<Item = FolderId> + '_ {
+        self.folders()
+            .filter(move |(_, f)| f.size <= size)
+            .map(|(id, _)| id)
     }
-    return Some(min_size);
 }
 
-pub fn create_file_system_from_cmd<'a>(input: &str) -> FileSystem {
-    let root: NodeHanlder<Folder> = Folder::new_rc("/".into(), None);
+/// Sums the sizes of every folder whose size is at most `size`
+pub fn find_sum_at_most(size: u64, file_system: &FileSystem) -> u64 {
+    file_system
+        .directories_at_most(size)
+        .map(|id| file_system.folder(id).size())
+        .sum()
+}
+
+/// Finds the directories that can be deleted to free up the desired size
+/// Returns the smallest size that can be freed (if any)
+pub fn find_smallest_to_free(min_required_size: u64, file_system: &FileSystem) -> Option<u64> {
+    file_system
+        .folders()
+        .map(|(_, f)| f.size())
+        .filter(|&size| size >= min_required_size)
+        .min()
+}
 
-    let mut current_folder = Rc::clone(&root);
+/// Rebuilds the file system from a terminal transcript. Listing a folder
+/// more than once merges the entries instead of duplicating them
+pub fn create_file_system_from_cmd(input: &str) -> Result<FileSystem, ParseError> {
+    let mut file_system = FileSystem::new();
 
-    let input = parse_input(&input);
+    let mut current_folder = FileSystem::ROOT;
 
-    for entry in input {
+    for (line, entry) in parse_input(input)? {
         match entry {
             ParseResult::Command(c) => match c {
                 Command::Ls => {}
-                Command::CdBack => {
-                    let parent = Rc::clone(
-                        current_folder
-                            .borrow()
-                            .parent
-                            .as_ref()
-                            .expect("Cannot go back"),
-                    );
-
-                    current_folder = parent;
-                }
-                Command::Cd(n) => {
-                    let mut tmp = None;
-
-                    for child in &current_folder.borrow().children {
-                        if child.borrow().name == n {
-                            tmp = Some(Rc::clone(child));
-                            break;
-                        }
-                    }
-
-                    if let Some(folder) = tmp {
-                        current_folder = folder;
-                    }
+                Command::Cd(path) => {
+                    current_folder = file_system
+                        .walk(current_folder, &path)
+                        .map_err(|kind| ParseError { line, kind })?;
                 }
             },
             ParseResult::File(f) => {
-                let mut folder = current_folder.borrow_mut();
-                folder.add_file(f);
+                file_system.add_file(current_folder, f);
             }
             ParseResult::Folder(f) => {
-                let folder = Folder::new_rc(f, Some(Rc::clone(&current_folder)));
-                // Aqui es donde muere
-                current_folder.borrow_mut().add_folder(folder);
+                file_system.add_folder(current_folder, f);
             }
         }
     }
 
-    FileSystem { root }
+    file_system.compute_sizes();
+    Ok(file_system)
 }
-fn parse_input(input: &str) -> Vec<ParseResult> {
+
+/// Parses every line of the transcript, pairing it with its 1-based line number
+fn parse_input(input: &str) -> Result<Vec<(usize, ParseResult)>, ParseError> {
     let mut results = vec![];
 
-    for line in input.lines().skip(1) {
-        if line.starts_with("$") {
-            let mut splits = line.split(" ").skip(1);
-            let command_str = splits.next().unwrap();
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |kind| ParseError {
+            line: line_number,
+            kind,
+        };
 
-            match command_str {
-                "ls" => {
-                    results.push(ParseResult::Command(Command::Ls));
-                }
-                "cd" => {
-                    let path = splits.next().unwrap();
+        if line.trim().is_empty() {
+            continue;
+        }
 
-                    if path == ".." {
-                        results.push(ParseResult::Command(Command::CdBack));
-                        continue;
-                    }
+        if let Some(command) = line.strip_prefix("$") {
+            let mut splits = command.split_whitespace();
+            let command_str = splits
+                .next()
+                .ok_or(error(ParseErrorKind::MissingArgument))?;
 
-                    results.push(ParseResult::Command(Command::Cd(String::from(path))));
+            let command = match command_str {
+                "ls" => Command::Ls,
+                "cd" => {
+                    let path = splits
+                        .next()
+                        .ok_or(error(ParseErrorKind::MissingArgument))?;
+                    Command::Cd(String::from(path))
                 }
-                _ => {
-                    panic!("Unknown command");
+                unknown => {
+                    return Err(error(ParseErrorKind::UnknownCommand(unknown.to_string())));
                 }
-            }
+            };
+            results.push((line_number, ParseResult::Command(command)));
             continue;
         }
 
-        let mut splits = line.split(" ");
+        let mut splits = line.split_whitespace();
+        let (kind, name) = match (splits.next(), splits.next()) {
+            (Some(kind), Some(name)) => (kind, name.to_string()),
+            _ => return Err(error(ParseErrorKind::MissingArgument)),
+        };
 
-        match splits.next().unwrap() {
+        match kind {
             "dir" => {
-                let name = splits.next().unwrap().to_string();
-                results.push(ParseResult::Folder(name));
+                results.push((line_number, ParseResult::Folder(name)));
             }
             number => {
-                let name = splits.next().unwrap().to_string();
                 let size = number
                     .parse::<u64>()
-                    .expect(&format!("Cannot parse number {}", number));
+                    .map_err(|_| error(ParseErrorKind::InvalidSize(number.to_string())))?;
 
-                results.push(ParseResult::File(File { name, size }));
+                results.push((line_number, ParseResult::File(File { name, size })));
             }
         }
     }
 
-    results
+    Ok(results)
 }
 
 #[cfg(test)]
@@ -238,6 +412,130 @@ mod tests {
     //     - d.ext (file, size=5626152)
     //     - k (file, size=7214296)
 
+    /// File system of the demo transcript. Reads the input of the current
+    /// mode, so tests check `is_demo_mode()` first
+    pub fn demo_file_system() -> FileSystem {
+        create_file_system_from_cmd(&get_input()).expect("Demo transcript should parse")
+    }
+
+    pub mod file_system {
+        use super::*;
+
+        #[test]
+        fn test_resolve() {
+            if !is_demo_mode() {
+                return;
+            }
+
+            let filesystem = demo_file_system();
+
+            let e = filesystem.resolve("/a/e").expect("/a/e should exist");
+            assert_eq!(filesystem.folder(e).name(), "e");
+            assert_eq!(filesystem.folder(e).size(), 584);
+            assert_eq!(filesystem.path(e), "/a/e");
+
+            assert_eq!(filesystem.resolve("/"), Some(FileSystem::ROOT));
+            assert!(filesystem.resolve("/a/x").is_none());
+        }
+
+        #[test]
+        fn test_directories_with_paths() {
+            if !is_demo_mode() {
+                return;
+            }
+
+            let filesystem = demo_file_system();
+
+            let paths = filesystem
+                .directories()
+                .into_iter()
+                .map(|id| (filesystem.path(id), filesystem.folder(id).size()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                paths,
+                vec![
+                    ("/".to_string(), 48381165),
+                    ("/a".to_string(), 94853),
+                    ("/a/e".to_string(), 584),
+                    ("/d".to_string(), 24933642),
+                ]
+            );
+        }
+
+        #[test]
+        fn test_cd_root_absolute_and_repeated_ls() {
+            let input = "$ cd /
+$ ls
+dir a
+10 b
+$ cd a
+$ ls
+dir e
+$ cd /a/e
+$ ls
+5 i
+$ cd /
+$ ls
+dir a
+10 b
+$ cd /a
+$ ls
+dir e
+";
+            let filesystem = create_file_system_from_cmd(input).unwrap();
+
+            assert_eq!(filesystem.root().files().len(), 1);
+            assert_eq!(filesystem.directories().len(), 3);
+            assert_eq!(filesystem.size(), 15);
+        }
+
+        #[test]
+        fn test_deep_generated_transcript() {
+            let depth = 250_000;
+            let mut input = String::from("$ cd /\n");
+            for _ in 0..depth {
+                input.push_str("$ ls\ndir d\n10 f\n$ cd d\n");
+            }
+
+            let filesystem = create_file_system_from_cmd(&input).unwrap();
+
+            assert_eq!(filesystem.size(), 10 * depth);
+            assert_eq!(find_sum_at_most(20, &filesystem), 10 + 20);
+            assert_eq!(find_smallest_to_free(25, &filesystem), Some(30));
+            assert_eq!(filesystem.directories_at_most(10).count(), 2);
+            assert_eq!(filesystem.directories().len() as u64, depth + 1);
+        }
+
+        #[test]
+        fn test_parse_errors() {
+            let unknown = create_file_system_from_cmd("$ cd /\n$ ls\ndir a\n$ cd b\n");
+            assert_eq!(
+                unknown.unwrap_err(),
+                ParseError {
+                    line: 4,
+                    kind: ParseErrorKind::UnknownDirectory("b".to_string())
+                }
+            );
+
+            let command = create_file_system_from_cmd("$ cd /\n$ rm -rf a\n");
+            assert_eq!(
+                command.unwrap_err(),
+                ParseError {
+                    line: 2,
+                    kind: ParseErrorKind::UnknownCommand("rm".to_string())
+                }
+            );
+
+            let size = create_file_system_from_cmd("$ ls\n12a b.txt\n").unwrap_err();
+            assert_eq!(size.line, 2);
+            assert_eq!(size.to_string(), "line 2: cannot parse size `12a`");
+
+            let above = create_file_system_from_cmd("$ cd /\n$ cd ..\n").unwrap_err();
+            assert_eq!(above.kind, ParseErrorKind::AboveRoot);
+        }
+    }
+
     pub mod part1 {
         use super::*;
 
@@ -251,15 +549,16 @@ mod tests {
 
             let demo_input_file_system: String = get_input();
 
-            let filesystem = create_file_system_from_cmd(&demo_input_file_system);
+            let filesystem = create_file_system_from_cmd(&demo_input_file_system)
+                .expect("Demo transcript should parse");
 
             assert_eq!(
-                filesystem.root.borrow().size(),
+                filesystem.size(),
                 expected_size,
                 "Size calculation do not match"
             );
 
-            let sum = find_sum_at_most(100000, &filesystem.root);
+            let sum = find_sum_at_most(100000, &filesystem);
 
             assert_eq!(sum, 95437, "Sum calculation do not match");
         }
@@ -272,9 +571,10 @@ mod tests {
 
             let input_file_system: String = get_input();
 
-            let filesystem = create_file_system_from_cmd(&input_file_system);
+            let filesystem =
+                create_file_system_from_cmd(&input_file_system).expect("Transcript should parse");
 
-            let sum = find_sum_at_most(100000, &filesystem.root);
+            let sum = find_sum_at_most(100000, &filesystem);
 
             println!("For part1: {}", sum)
         }
@@ -294,12 +594,13 @@ mod tests {
 
             let input_file_system: String = get_input();
 
-            let filesystem = create_file_system_from_cmd(&input_file_system);
-            let total_mem = filesystem.root.borrow().size();
+            let filesystem =
+                create_file_system_from_cmd(&input_file_system).expect("Transcript should parse");
+            let total_mem = filesystem.size();
             let free_total = TOTAL_SPACE_DRIVE - total_mem;
             let need_to_delete = MIN_FREE_SPACE - free_total;
 
-            let smallest_space = find_smallest_to_free(need_to_delete, &filesystem.root).unwrap();
+            let smallest_space = find_smallest_to_free(need_to_delete, &filesystem).unwrap();
 
             assert_eq!(
                 smallest_space, 24933642,
@@ -315,12 +616,13 @@ mod tests {
 
             let input_file_system: String = get_input();
 
-            let filesystem = create_file_system_from_cmd(&input_file_system);
-            let total_mem = filesystem.root.borrow().size();
+            let filesystem =
+                create_file_system_from_cmd(&input_file_system).expect("Transcript should parse");
+            let total_mem = filesystem.size();
             let free_total = TOTAL_SPACE_DRIVE - total_mem;
             let need_to_delete = MIN_FREE_SPACE - free_total;
 
-            let smallest_space = find_smallest_to_free(need_to_delete, &filesystem.root).unwrap();
+            let smallest_space = find_smallest_to_free(need_to_delete, &filesystem).unwrap();
 
             println!("For part2: {}", smallest_space)
         }
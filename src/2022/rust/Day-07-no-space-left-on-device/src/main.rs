@@ -0,0 +1,45 @@
+use std::{env, path::Path};
+
+use day_07_no_space_left_on_device::{
+    create_file_system_from_cmd, generate_transcript, TranscriptOptions,
+};
+use utility_2022::get_input;
+
+/// Usage: `cargo run -- [tree|du|json]` or `cargo run -- transcript DIR`
+///
+/// Rebuilds the file system from the puzzle input and prints it in the chosen
+/// format, so the parsed tree can be checked by eye. Defaults to `tree`.
+/// `transcript` prints the terminal session that would list DIR instead
+fn main() {
+    if env::args().nth(1).as_deref() == Some("transcript") {
+        let dir = env::args().nth(2).unwrap_or_else(|| ".".to_string());
+        match generate_transcript(Path::new(&dir), &TranscriptOptions::default()) {
+            Ok(transcript) => print!("{}", transcript),
+            Err(e) => {
+                eprintln!("Cannot read {}: {}", dir, e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let input = get_input();
+
+    let file_system = match create_file_system_from_cmd(&input) {
+        Ok(file_system) => file_system,
+        Err(e) => {
+            eprintln!("Cannot parse transcript: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match env::args().nth(1).as_deref().unwrap_or("tree") {
+        "tree" => println!("{}", file_system.render_tree()),
+        "du" => println!("{}", file_system.render_du()),
+        "json" => println!("{}", file_system.to_json()),
+        other => {
+            eprintln!("Unknown report `{}`, expected tree, du or json", other);
+            std::process::exit(1);
+        }
+    }
+}
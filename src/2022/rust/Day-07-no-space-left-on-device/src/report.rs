@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Entry, File, FileSystem, FolderId};
+
+/// One entry of the file system in a flat list, parents before children.
+/// `parent` is the position of the parent folder in that list, so importing
+/// and exporting never recurse, however deep the tree is
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Node {
+    Dir {
+        name: String,
+        parent: Option<usize>,
+    },
+    File {
+        name: String,
+        size: u64,
+        parent: usize,
+    },
+}
+
+/// An entry met while walking the tree, files come with their folder
+enum Visit<'a> {
+    Folder(FolderId),
+    File(FolderId, &'a File),
+}
+
+/// Formats a size the way `du -h` does: rounded up, one decimal below 10
+fn human_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["K", "M", "G", "T", "P"];
+
+    if size < 1024 {
+        return size.to_string();
+    }
+
+    let mut value = size as f64;
+    let mut unit = "";
+    for u in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = u;
+    }
+
+    let tenths = (value * 10.0).ceil();
+    if tenths < 100.0 {
+        format!("{:.1}{}", tenths / 10.0, unit)
+    } else {
+        format!("{}{}", value.ceil(), unit)
+    }
+}
+
+impl FileSystem {
+    /// Visits the folder `id` and everything below it in pre-order, in the
+    /// order `ls` listed the entries, along with their depth. Keeps its own
+    /// stack, so deep trees cannot overflow the call stack
+    fn visit(&self, id: FolderId, mut f: impl FnMut(Visit, usize)) {
+        let mut pending = vec![(Entry::Folder(id), id, 0)];
+
+        while let Some((entry, folder, depth)) = pending.pop() {
+            match entry {
+                Entry::Folder(child) => {
+                    f(Visit::Folder(child), depth);
+                    let entries = self.folder(child).entries().iter().rev();
+                    pending.extend(entries.map(|&e| (e, child, depth + 1)));
+                }
+                Entry::File(index) => f(
+                    Visit::File(folder, &self.folder(folder).files()[index]),
+                    depth,
+                ),
+            }
+        }
+    }
+
+    /// Renders the tree in the puzzle's format, e.g. `- a (dir)` and
+    /// `- i (file, size=584)`, indenting two spaces per level
+    pub fn render_tree(&self) -> String {
+        let mut lines = vec![];
+
+        self.visit(FileSystem::ROOT, |visit, depth| {
+            let line = match visit {
+                Visit::Folder(id) => format!("- {} (dir)", self.folder(id).name()),
+                Visit::File(_, file) => format!("- {} (file, size={})", file.name, file.size),
+            };
+            lines.push(format!("{}{}", "  ".repeat(depth), line));
+        });
+
+        lines.join("\n")
+    }
+
+    /// Renders a `du -h` style table with one folder per line, biggest first
+    pub fn render_du(&self) -> String {
+        let mut directories = self.directories();
+        // Paths are only built to break ties and to print each line
+        directories.sort_by(|&a, &b| {
+            let (size_a, size_b) = (self.folder(a).size(), self.folder(b).size());
+            size_b
+                .cmp(&size_a)
+                .then_with(|| self.path(a).cmp(&self.path(b)))
+        });
+
+        directories
+            .iter()
+            .map(|&id| format!("{}\t{}", human_size(self.folder(id).size()), self.path(id)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Lists the tree below a folder as [`Node`]s, the folder itself first
+    pub fn to_nodes(&self, id: FolderId) -> Vec<Node> {
+        let mut nodes = vec![];
+        let mut positions: HashMap<FolderId, usize> = HashMap::new();
+
+        self.visit(id, |visit, _| match visit {
+            Visit::Folder(folder) => {
+                let parent = match folder == id {
+                    true => None,
+                    false => self.folder(folder).parent().map(|p| positions[&p]),
+                };
+                positions.insert(folder, nodes.len());
+                nodes.push(Node::Dir {
+                    name: self.folder(folder).name().to_string(),
+                    parent,
+                });
+            }
+            Visit::File(folder, file) => nodes.push(Node::File {
+                name: file.name.clone(),
+                size: file.size,
+                parent: positions[&folder],
+            }),
+        });
+
+        nodes
+    }
+
+    /// Builds a file system from [`Node`]s listed like `to_nodes` does. Fails
+    /// if the first node is not the root folder or a parent is not a folder
+    /// listed earlier
+    pub fn from_nodes(nodes: &[Node]) -> Result<FileSystem, String> {
+        let mut file_system = FileSystem::new();
+        // The folder created for every node that is a folder
+        let mut folders: Vec<Option<FolderId>> = Vec::with_capacity(nodes.len());
+
+        for (position, node) in nodes.iter().enumerate() {
+            let parent = match node {
+                Node::Dir { parent: None, .. } if position == 0 => None,
+                Node::Dir {
+                    parent: Some(parent),
+                    ..
+                }
+                | Node::File { parent, .. } => {
+                    let folder = folders.get(*parent).copied().flatten();
+                    Some(folder.ok_or_else(|| {
+                        format!("Node {} has no folder at position {}", position, parent)
+                    })?)
+                }
+                _ => return Err(format!("Node {} should be the only root folder", position)),
+            };
+
+            folders.push(match (node, parent) {
+                (Node::Dir { .. }, None) => Some(FileSystem::ROOT),
+                (Node::Dir { name, .. }, Some(parent)) => {
+                    Some(file_system.add_folder(parent, name.clone()))
+                }
+                (Node::File { name, size, .. }, Some(parent)) => {
+                    file_system.add_file(
+                        parent,
+                        File {
+                            name: name.clone(),
+                            size: *size,
+                        },
+                    );
+                    None
+                }
+                (Node::File { .. }, None) => unreachable!("Files always have a parent"),
+            });
+        }
+
+        if nodes.is_empty() {
+            return Err("There is no root folder".to_string());
+        }
+
+        file_system.compute_sizes();
+        Ok(file_system)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_nodes(FileSystem::ROOT))
+            .expect("File system should always serialize")
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<FileSystem> {
+        let nodes: Vec<Node> = serde_json::from_str(json)?;
+        FileSystem::from_nodes(&nodes).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utility_2022::is_demo_mode;
+
+    use crate::{create_file_system_from_cmd, tests::demo_file_system};
+
+    use super::*;
+
+    const EXPECTED_TREE: &str = "- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - j (file, size=4060174)
+    - d.log (file, size=8033020)
+    - d.ext (file, size=5626152)
+    - k (file, size=7214296)";
+
+    #[test]
+    fn test_render_tree() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        assert_eq!(demo_file_system().render_tree(), EXPECTED_TREE);
+    }
+
+    #[test]
+    fn test_render_du() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        assert_eq!(
+            demo_file_system().render_du(),
+            "47M\t/\n24M\t/d\n93K\t/a\n584\t/a/e"
+        );
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(1024), "1.0K");
+        assert_eq!(human_size(1536), "1.5K");
+        assert_eq!(human_size(10 * 1024 - 1), "10K");
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        let file_system = demo_file_system();
+
+        let json = file_system.to_json();
+        let imported = FileSystem::from_json(&json).expect("Exported JSON should import");
+
+        assert_eq!(imported.to_json(), json);
+        assert_eq!(imported.render_tree(), EXPECTED_TREE);
+        assert_eq!(imported.size(), file_system.size());
+    }
+
+    #[test]
+    fn test_deep_tree_round_trip() {
+        // Deep enough that one stack frame per level would overflow the small
+        // stack below, and far past serde_json's nesting limit
+        let depth: u64 = 3_000;
+        let mut input = String::from("$ cd /\n");
+        for _ in 0..depth {
+            input.push_str("$ ls\ndir d\n10 f\n$ cd d\n");
+        }
+
+        let walks = std::thread::Builder::new()
+            .stack_size(128 * 1024)
+            .spawn(move || {
+                let file_system = create_file_system_from_cmd(&input).unwrap();
+                let json = file_system.to_json();
+                let imported = FileSystem::from_json(&json).expect("Exported JSON should import");
+
+                assert_eq!(imported.to_json(), json);
+                assert_eq!(imported.render_tree(), file_system.render_tree());
+                assert_eq!(imported.render_du(), file_system.render_du());
+                assert_eq!(imported.size(), 10 * depth);
+                assert_eq!(imported.render_tree().lines().count() as u64, 2 * depth + 1);
+            })
+            .unwrap();
+
+        walks.join().expect("Deep tree walks should not overflow");
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        let child_first = r#"[{"type": "file", "name": "f", "size": 1, "parent": 0}]"#;
+        assert!(FileSystem::from_json(child_first).is_err());
+
+        let forward = r#"[
+            {"type": "dir", "name": "/", "parent": null},
+            {"type": "file", "name": "f", "size": 1, "parent": 2},
+            {"type": "dir", "name": "d", "parent": 0}
+        ]"#;
+        assert!(FileSystem::from_json(forward).is_err());
+
+        let file_parent = r#"[
+            {"type": "dir", "name": "/", "parent": null},
+            {"type": "file", "name": "f", "size": 1, "parent": 0},
+            {"type": "file", "name": "g", "size": 1, "parent": 1}
+        ]"#;
+        assert!(FileSystem::from_json(file_parent).is_err());
+
+        assert!(FileSystem::from_json("[]").is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use std::{collections::VecDeque, fs, io, path::Path};
+
+/// Order in which the generated transcript visits folders
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Traversal {
+    /// `cd` into every subfolder right after listing it and `cd ..` back out
+    DepthFirst,
+    /// List the tree level by level, moving around with absolute `cd /a/b`
+    BreadthFirst,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TranscriptOptions {
+    pub traversal: Traversal,
+    /// Run `ls` twice in every folder, to exercise merging of repeated listings
+    pub repeat_ls: bool,
+}
+
+impl Default for TranscriptOptions {
+    fn default() -> Self {
+        TranscriptOptions {
+            traversal: Traversal::DepthFirst,
+            repeat_ls: false,
+        }
+    }
+}
+
+/// Snapshot of a directory on disk, with entries sorted by name
+#[derive(Debug, Default)]
+struct DiskFolder {
+    files: Vec<(String, u64)>,
+    folders: Vec<(String, DiskFolder)>,
+}
+
+impl DiskFolder {
+    /// Reads a directory recursively, skipping symlinks. Fails on names the
+    /// transcript cannot represent: not UTF-8 or containing whitespace
+    fn read(path: &Path) -> io::Result<DiskFolder> {
+        let mut folder = DiskFolder::default();
+
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let name = match entry.file_name().into_string() {
+                Ok(name) if !name.contains(char::is_whitespace) => name,
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "{} cannot be written in a transcript",
+                            entry.path().display()
+                        ),
+                    ))
+                }
+            };
+
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                folder
+                    .folders
+                    .push((name, DiskFolder::read(&entry.path())?));
+            } else if file_type.is_file() {
+                folder.files.push((name, entry.metadata()?.len()));
+            }
+        }
+
+        folder.files.sort();
+        folder.folders.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(folder)
+    }
+
+    fn push_ls(&self, lines: &mut Vec<String>, options: &TranscriptOptions) {
+        let times = if options.repeat_ls { 2 } else { 1 };
+
+        for _ in 0..times {
+            lines.push("$ ls".to_string());
+            for (name, _) in &self.folders {
+                lines.push(format!("dir {}", name));
+            }
+            for (name, size) in &self.files {
+                lines.push(format!("{} {}", size, name));
+            }
+        }
+    }
+
+    fn push_depth_first(&self, lines: &mut Vec<String>, options: &TranscriptOptions) {
+        self.push_ls(lines, options);
+
+        for (name, folder) in &self.folders {
+            lines.push(format!("$ cd {}", name));
+            folder.push_depth_first(lines, options);
+            lines.push("$ cd ..".to_string());
+        }
+    }
+
+    fn push_breadth_first(&self, lines: &mut Vec<String>, options: &TranscriptOptions) {
+        let mut pending = VecDeque::from([(String::from("/"), self)]);
+
+        while let Some((path, folder)) = pending.pop_front() {
+            lines.push(format!("$ cd {}", path));
+            folder.push_ls(lines, options);
+
+            for (name, child) in &folder.folders {
+                let child_path = match path.as_str() {
+                    "/" => format!("/{}", name),
+                    _ => format!("{}/{}", path, name),
+                };
+                pending.push_back((child_path, child));
+            }
+        }
+    }
+}
+
+/// Walks a directory on disk and writes the terminal session that would list
+/// it, using `root` as `/`. Fails if a name cannot be written in a transcript
+pub fn generate_transcript(root: &Path, options: &TranscriptOptions) -> io::Result<String> {
+    let folder = DiskFolder::read(root)?;
+    let mut lines = vec![];
+
+    match options.traversal {
+        Traversal::DepthFirst => {
+            lines.push("$ cd /".to_string());
+            folder.push_depth_first(&mut lines, options);
+        }
+        Traversal::BreadthFirst => folder.push_breadth_first(&mut lines, options),
+    }
+
+    lines.push(String::new());
+    Ok(lines.join("\n"))
+}
+
+/// Total size of every file below a directory on disk, skipping symlinks.
+/// Walks the disk on its own, so it can check [`generate_transcript`]
+pub fn disk_usage(root: &Path) -> io::Result<u64> {
+    let mut pending = vec![root.to_path_buf()];
+    let mut total = 0;
+
+    while let Some(path) = pending.pop() {
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                total += entry.metadata()?.len();
+            }
+        }
+    }
+
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, path::PathBuf, process};
+
+    use crate::create_file_system_from_cmd;
+
+    use super::*;
+
+    /// Builds a small directory tree under the system temp dir
+    fn sample_tree(name: &str) -> PathBuf {
+        let root = env::temp_dir().join(format!("day-07-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        fs::create_dir_all(root.join("a/e")).unwrap();
+        fs::create_dir_all(root.join("d")).unwrap();
+        fs::create_dir_all(root.join("empty")).unwrap();
+        fs::write(root.join("b.txt"), vec![0; 1500]).unwrap();
+        fs::write(root.join("a/f"), vec![0; 300]).unwrap();
+        fs::write(root.join("a/e/i"), vec![0; 58]).unwrap();
+        fs::write(root.join("d/j"), vec![0; 4060]).unwrap();
+
+        root
+    }
+
+    #[test]
+    fn test_transcripts_match_disk() {
+        let root = sample_tree("transcript");
+
+        for traversal in [Traversal::DepthFirst, Traversal::BreadthFirst] {
+            for repeat_ls in [false, true] {
+                let options = TranscriptOptions {
+                    traversal,
+                    repeat_ls,
+                };
+                let transcript = generate_transcript(&root, &options).unwrap();
+                let file_system = create_file_system_from_cmd(&transcript)
+                    .expect("Generated transcript should parse");
+
+                assert_eq!(file_system.size(), 1500 + 300 + 58 + 4060);
+                assert_eq!(file_system.size(), disk_usage(&root).unwrap());
+
+                for id in file_system.directories() {
+                    let path = file_system.path(id);
+                    let on_disk = root.join(path.trim_start_matches('/'));
+                    assert_eq!(
+                        file_system.folder(id).size(),
+                        disk_usage(&on_disk).unwrap(),
+                        "Size mismatch for {} with {:?}",
+                        path,
+                        options
+                    );
+                }
+                assert_eq!(file_system.directories().len(), 5);
+            }
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_unrepresentable_names() {
+        let root = sample_tree("whitespace");
+        fs::write(root.join("d/with space"), vec![0; 7]).unwrap();
+
+        let error = generate_transcript(&root, &TranscriptOptions::default()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("with space"));
+        assert_eq!(disk_usage(&root).unwrap(), 1500 + 300 + 58 + 4060 + 7);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_depth_first_transcript() {
+        let root = sample_tree("depth-first");
+
+        let transcript = generate_transcript(&root, &TranscriptOptions::default()).unwrap();
+
+        assert_eq!(
+            transcript,
+            "$ cd /
+$ ls
+dir a
+dir d
+dir empty
+1500 b.txt
+$ cd a
+$ ls
+dir e
+300 f
+$ cd e
+$ ls
+58 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060 j
+$ cd ..
+$ cd empty
+$ ls
+$ cd ..
+"
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::CampAnalysis;
+
+/// Which computed field of the camp to export
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    /// White for trees visible from outside, black for hidden ones
+    Visibility,
+    /// Scenic score scaled so the best spot is white
+    ScenicScore,
+}
+
+/// A treehouse candidate, ordered by score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Spot {
+    pub score: u32,
+    /// Reversed so that, for equal scores, the spot nearer the top left wins
+    position: Reverse<(usize, usize)>,
+}
+
+impl Spot {
+    pub fn row(&self) -> usize {
+        self.position.0 .0
+    }
+
+    pub fn col(&self) -> usize {
+        self.position.0 .1
+    }
+}
+
+impl CampAnalysis {
+    /// Brightness between 0 and 255 of every tree for the given field, row by row
+    pub fn intensities(&self, field: Field) -> Vec<u8> {
+        match field {
+            Field::Visibility => self
+                .iter()
+                .map(|(_, _, view)| if view.visible { 255 } else { 0 })
+                .collect(),
+            Field::ScenicScore => {
+                let max = self.max_scenic_score().unwrap_or(0).max(1) as u64;
+                self.iter()
+                    .map(|(_, _, view)| (view.scenic_score() as u64 * 255 / max) as u8)
+                    .collect()
+            }
+        }
+    }
+
+    /// Encodes a field as a binary (P5) PGM image, one pixel per tree
+    pub fn to_pgm(&self, field: Field) -> Vec<u8> {
+        let mut image = format!("P5\n{} {}\n255\n", self.num_cols, self.num_rows).into_bytes();
+        image.extend(self.intensities(field));
+        image
+    }
+
+    /// Draws a field in the terminal with ANSI grayscale backgrounds, two
+    /// columns per tree so the grid looks square
+    pub fn render_terminal(&self, field: Field) -> String {
+        let intensities = self.intensities(field);
+        let mut output = String::new();
+
+        for row in intensities.chunks(self.num_cols.max(1)) {
+            for &value in row {
+                // The 256 colour palette has 24 grays, from 232 to 255
+                let gray = 232 + value as u32 * 23 / 255;
+                output.push_str(&format!("\x1b[48;5;{}m  ", gray));
+            }
+            output.push_str("\x1b[0m\n");
+        }
+
+        output
+    }
+
+    /// The `k` best treehouse spots, best first. Ties go to the spot nearer the
+    /// top left. Keeps a heap of `k` spots, so it is O(rows·cols·log k)
+    pub fn top_scenic_spots(&self, k: usize) -> Vec<Spot> {
+        let mut heap: BinaryHeap<Reverse<Spot>> = BinaryHeap::with_capacity(k + 1);
+
+        for (row, col, view) in self.iter() {
+            heap.push(Reverse(Spot {
+                score: view.scenic_score(),
+                position: Reverse((row, col)),
+            }));
+            if heap.len() > k {
+                heap.pop();
+            }
+        }
+
+        heap.into_sorted_vec().into_iter().map(|r| r.0).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utility_2022::is_demo_mode;
+
+    use crate::tests::demo_analysis;
+
+    use super::*;
+
+    #[test]
+    fn test_pgm() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        let analysis = demo_analysis();
+
+        let image = analysis.to_pgm(Field::Visibility);
+        let header = b"P5\n5 5\n255\n";
+        assert_eq!(&image[..header.len()], header);
+
+        let pixels = &image[header.len()..];
+        assert_eq!(pixels.len(), 25);
+        assert_eq!(pixels.iter().filter(|&&p| p == 255).count(), 21);
+
+        let heatmap = analysis.intensities(Field::ScenicScore);
+        assert_eq!(heatmap[3 * 5 + 2], 255);
+        assert_eq!(heatmap[0], 0);
+    }
+
+    #[test]
+    fn test_render_terminal() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        let grid = demo_analysis().render_terminal(Field::Visibility);
+
+        assert_eq!(grid.lines().count(), 5);
+        assert_eq!(grid.matches("\x1b[48;5;232m").count(), 4);
+    }
+
+    #[test]
+    fn test_top_scenic_spots() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        let spots = demo_analysis().top_scenic_spots(3);
+
+        let spots = spots
+            .iter()
+            .map(|s| (s.row(), s.col(), s.score))
+            .collect::<Vec<_>>();
+        assert_eq!(spots, vec![(3, 2, 8), (2, 1, 6), (1, 2, 4)]);
+    }
+}
@@ -0,0 +1,371 @@
+use std::fmt;
+
+/// Tree heights on a grid with any number of axes, stored row-major (the
+/// last axis changes fastest). Lines of sight run along every axis, in both
+/// directions
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeightMap {
+    dims: Vec<usize>,
+    heights: Vec<u32>,
+}
+
+/// Why [`HeightMap::parse`] rejected its input. Layers and rows are counted
+/// from 0
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// A character that is not a digit
+    InvalidHeight {
+        layer: usize,
+        row: usize,
+        found: char,
+    },
+    /// A row whose length differs from the first row
+    RowLength {
+        layer: usize,
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A layer whose number of rows differs from the first layer
+    LayerLength {
+        layer: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidHeight { layer, row, found } => {
+                write!(
+                    f,
+                    "layer {}, row {}: `{}` is not a height",
+                    layer, row, found
+                )
+            }
+            ParseError::RowLength {
+                layer,
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "layer {}, row {}: {} trees, expected {}",
+                layer, row, found, expected
+            ),
+            ParseError::LayerLength {
+                layer,
+                expected,
+                found,
+            } => write!(f, "layer {}: {} rows, expected {}", layer, found, expected),
+        }
+    }
+}
+
+/// Visibility and viewing distances of every tree in a [`HeightMap`]
+#[derive(Debug)]
+pub struct HeightMapAnalysis {
+    dims: Vec<usize>,
+    visible: Vec<bool>,
+    /// For every axis, the viewing distance of every tree towards the start
+    /// and towards the end of that axis
+    distances: Vec<Vec<(u32, u32)>>,
+}
+
+impl HeightMap {
+    /// Builds a height map from its size along each axis and the heights in
+    /// row-major order
+    pub fn new(dims: Vec<usize>, heights: Vec<u32>) -> HeightMap {
+        assert_eq!(
+            dims.iter().product::<usize>(),
+            heights.len(),
+            "Dimensions do not match the number of heights"
+        );
+        HeightMap { dims, heights }
+    }
+
+    /// Parses 2D layers of digits separated by blank lines. A single layer
+    /// gives a 2D map, several layers give a 3D map indexed `[layer, row, col]`.
+    /// Every row and every layer must have the same length as the first one
+    pub fn parse(input: &str) -> Result<HeightMap, ParseError> {
+        let layers = input
+            .split("\n\n")
+            .map(|layer| {
+                layer
+                    .lines()
+                    .filter(|row| !row.is_empty())
+                    .collect::<Vec<&str>>()
+            })
+            .filter(|layer| !layer.is_empty())
+            .collect::<Vec<_>>();
+
+        let num_rows = layers.first().map_or(0, |l| l.len());
+        let num_cols = layers
+            .first()
+            .and_then(|l| l.first())
+            .map_or(0, |r| r.chars().count());
+        let mut heights = vec![];
+
+        for (layer, rows) in layers.iter().enumerate() {
+            if rows.len() != num_rows {
+                return Err(ParseError::LayerLength {
+                    layer,
+                    expected: num_rows,
+                    found: rows.len(),
+                });
+            }
+
+            for (row, line) in rows.iter().enumerate() {
+                let len = line.chars().count();
+                if len != num_cols {
+                    return Err(ParseError::RowLength {
+                        layer,
+                        row,
+                        expected: num_cols,
+                        found: len,
+                    });
+                }
+
+                for c in line.chars() {
+                    let height = c.to_digit(10).ok_or(ParseError::InvalidHeight {
+                        layer,
+                        row,
+                        found: c,
+                    })?;
+                    heights.push(height);
+                }
+            }
+        }
+
+        Ok(match layers.len() {
+            1 => HeightMap::new(vec![num_rows, num_cols], heights),
+            n => HeightMap::new(vec![n, num_rows, num_cols], heights),
+        })
+    }
+
+    pub fn dims(&self) -> &[usize] {
+        &self.dims
+    }
+
+    pub fn heights(&self) -> &[u32] {
+        &self.heights
+    }
+
+    /// Computes visibility and viewing distances in O(cells·axes)
+    pub fn analyze(&self) -> HeightMapAnalysis {
+        let mut visible = vec![false; self.heights.len()];
+        let mut distances = vec![];
+
+        for axis in 0..self.dims.len() {
+            let mut axis_distances = vec![(0, 0); self.heights.len()];
+
+            for line in self.lines(axis) {
+                let heights = line.iter().map(|&i| self.heights[i]).collect::<Vec<u32>>();
+                let reversed = heights.iter().rev().copied().collect::<Vec<u32>>();
+
+                let backward = sweep_line(&heights);
+                let forward = sweep_line(&reversed).into_iter().rev();
+
+                for ((&cell, (back, back_visible)), (front, front_visible)) in
+                    line.iter().zip(backward).zip(forward)
+                {
+                    axis_distances[cell] = (back, front);
+                    visible[cell] |= back_visible || front_visible;
+                }
+            }
+
+            distances.push(axis_distances);
+        }
+
+        HeightMapAnalysis {
+            dims: self.dims.clone(),
+            visible,
+            distances,
+        }
+    }
+
+    /// Flat indices of every line running along `axis`
+    fn lines(&self, axis: usize) -> impl Iterator<Item = Vec<usize>> + '_ {
+        let stride: usize = self.dims[axis + 1..].iter().product();
+        let len = self.dims[axis];
+
+        (0..self.heights.len())
+            .filter(move |i| (i / stride).is_multiple_of(len))
+            .map(move |start| (0..len).map(|k| start + k * stride).collect())
+    }
+}
+
+impl HeightMapAnalysis {
+    pub fn dims(&self) -> &[usize] {
+        &self.dims
+    }
+
+    /// Flat row-major index of a coordinate
+    pub fn index(&self, coords: &[usize]) -> usize {
+        coords
+            .iter()
+            .zip(&self.dims)
+            .fold(0, |index, (&c, &dim)| index * dim + c)
+    }
+
+    /// Whether the tree can be seen from outside along any axis
+    pub fn is_visible(&self, index: usize) -> bool {
+        self.visible[index]
+    }
+
+    /// Viewing distances along `axis`, towards its start and towards its end
+    pub fn distances(&self, index: usize, axis: usize) -> (u32, u32) {
+        self.distances[axis][index]
+    }
+
+    /// Product of the viewing distances in every direction
+    pub fn scenic_score(&self, index: usize) -> u32 {
+        self.distances
+            .iter()
+            .map(|axis| axis[index].0 * axis[index].1)
+            .product()
+    }
+
+    pub fn visible_count(&self) -> usize {
+        self.visible.iter().filter(|&&v| v).count()
+    }
+
+    pub fn max_scenic_score(&self) -> Option<u32> {
+        (0..self.visible.len()).map(|i| self.scenic_score(i)).max()
+    }
+}
+
+/// Looks back from every tree of a line towards its start. Returns, for each
+/// tree, the viewing distance and whether it can be seen from the start.
+/// Uses a stack of trees with decreasing heights, so it runs in O(len)
+fn sweep_line(heights: &[u32]) -> Vec<(u32, bool)> {
+    let mut stack: Vec<usize> = vec![];
+
+    heights
+        .iter()
+        .enumerate()
+        .map(|(i, &height)| {
+            while let Some(&top) = stack.last() {
+                if heights[top] >= height {
+                    break;
+                }
+                stack.pop();
+            }
+
+            let result = match stack.last() {
+                Some(&blocker) => ((i - blocker) as u32, false),
+                None => (i as u32, true),
+            };
+            stack.push(i);
+            result
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(map: &HeightMap, coords: &[usize]) -> usize {
+        coords
+            .iter()
+            .zip(&map.dims)
+            .fold(0, |index, (&c, &dim)| index * dim + c)
+    }
+
+    /// Walks from `coords` in direction `step` along `axis`, one tree at a time
+    fn brute_force(map: &HeightMap, coords: &[usize], axis: usize, step: isize) -> (u32, bool) {
+        let height = map.heights[index(map, coords)];
+        let mut current = coords.to_vec();
+        let mut distance = 0;
+
+        loop {
+            let next = current[axis] as isize + step;
+            if next < 0 || next >= map.dims[axis] as isize {
+                return (distance, true);
+            }
+            current[axis] = next as usize;
+            distance += 1;
+            if map.heights[index(map, &current)] >= height {
+                return (distance, false);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_layers() {
+        let map = HeightMap::parse("123\n456\n\n789\n012\n").unwrap();
+
+        assert_eq!(map.dims(), &[2, 2, 3]);
+        assert_eq!(map.heights(), &[1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2]);
+        assert_eq!(HeightMap::parse("30373\n25512\n").unwrap().dims(), &[2, 5]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            HeightMap::parse("123\n45\n"),
+            Err(ParseError::RowLength {
+                layer: 0,
+                row: 1,
+                expected: 3,
+                found: 2,
+            })
+        );
+        assert_eq!(
+            HeightMap::parse("12\n34\n\n56\n"),
+            Err(ParseError::LayerLength {
+                layer: 1,
+                expected: 2,
+                found: 1,
+            })
+        );
+        assert_eq!(
+            HeightMap::parse("12\n3x\n"),
+            Err(ParseError::InvalidHeight {
+                layer: 0,
+                row: 1,
+                found: 'x',
+            })
+        );
+    }
+
+    #[test]
+    fn test_3d_matches_brute_force() {
+        let dims = vec![4, 5, 3];
+        // Small linear congruential generator, so the forest is the same every run
+        let mut seed: u32 = 7;
+        let heights = (0..60)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (seed >> 16) % 10
+            })
+            .collect();
+        let map = HeightMap::new(dims, heights);
+        let analysis = map.analyze();
+
+        for a in 0..4 {
+            for b in 0..5 {
+                for c in 0..3 {
+                    let coords = [a, b, c];
+                    let index = analysis.index(&coords);
+                    let mut visible = false;
+                    let mut score = 1;
+
+                    for axis in 0..3 {
+                        let back = brute_force(&map, &coords, axis, -1);
+                        let front = brute_force(&map, &coords, axis, 1);
+
+                        assert_eq!(analysis.distances(index, axis), (back.0, front.0));
+                        visible |= back.1 || front.1;
+                        score *= back.0 * front.0;
+                    }
+
+                    assert_eq!(analysis.is_visible(index), visible, "At {:?}", coords);
+                    assert_eq!(analysis.scenic_score(index), score, "At {:?}", coords);
+                }
+            }
+        }
+    }
+}
@@ -1,57 +1,146 @@
-type TreeCamp = Vec<Vec<u32>>;
-
-// Tienes que checar si el arbolito que estas viendo es mas alto que los que estan a su alrededor
-// Pero va desde su posicion hasta que sale
-pub fn is_tile_visible(x: i32, y: i32, camp: &TreeCamp) -> bool {
-    let current_height = &camp.get(x as usize).unwrap().get(y as usize).unwrap();
+mod export;
+mod height_map;
+
+pub use export::{Field, Spot};
+pub use height_map::{HeightMap, HeightMapAnalysis, ParseError};
+
+pub type TreeCamp = Vec<Vec<u32>>;
+
+/// What can be seen from a single tree
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TreeView {
+    pub height: u32,
+    /// Visible from outside the camp in at least one direction
+    pub visible: bool,
+    /// Viewing distances, counted in trees until the edge or a tree at least as tall
+    pub up: u32,
+    pub down: u32,
+    pub left: u32,
+    pub right: u32,
+}
 
-    // Check sides
+impl TreeView {
+    pub fn scenic_score(&self) -> u32 {
+        self.up * self.down * self.left * self.right
+    }
+}
 
-    let row = camp.get(x as usize).unwrap();
+/// Visibility and viewing distances of every tree in a camp
+#[derive(Debug)]
+pub struct CampAnalysis {
+    pub num_rows: usize,
+    pub num_cols: usize,
+    views: Vec<TreeView>,
+}
 
-    let splits = row.split_at(y as usize);
-    // Check left
-    let max_left = splits.0.iter().max().unwrap();
+impl CampAnalysis {
+    /// The view from the tree at row `x`, column `y`
+    pub fn get(&self, x: usize, y: usize) -> &TreeView {
+        &self.views[x * self.num_cols + y]
+    }
 
-    if *current_height > max_left {
-        return true;
+    /// Every tree as `(row, col, view)`, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &TreeView)> {
+        self.views
+            .iter()
+            .enumerate()
+            .map(move |(i, view)| (i / self.num_cols, i % self.num_cols, view))
     }
 
-    let max_right = splits.1.iter().skip(1).max().unwrap();
+    pub fn visible_count(&self) -> usize {
+        self.views.iter().filter(|v| v.visible).count()
+    }
 
-    if *current_height > max_right {
-        return true;
+    pub fn max_scenic_score(&self) -> Option<u32> {
+        self.views.iter().map(|v| v.scenic_score()).max()
     }
+}
 
-    let mut max_up = 0;
+/// Size of the camp as `(rows, cols)`, or an error for a row whose length
+/// differs from the first one
+fn camp_size(camp: &TreeCamp) -> Result<(usize, usize), ParseError> {
+    let num_cols = camp.first().map_or(0, |row| row.len());
+
+    match camp.iter().position(|row| row.len() != num_cols) {
+        Some(row) => Err(ParseError::RowLength {
+            layer: 0,
+            row,
+            expected: num_cols,
+            found: camp[row].len(),
+        }),
+        None => Ok((camp.len(), num_cols)),
+    }
+}
 
-    for i in 0..(x as usize) {
-        let cmp_tree = camp.get(i).unwrap().get(y as usize).unwrap();
+/// Computes visibility and viewing distances for the whole camp in O(rows·cols),
+/// as a 2D [`HeightMap`]
+pub fn analyze_camp(camp: &TreeCamp) -> Result<CampAnalysis, ParseError> {
+    let (num_rows, num_cols) = camp_size(camp)?;
 
-        if cmp_tree > &max_up {
-            max_up = *cmp_tree;
-        }
-    }
+    let map = HeightMap::new(vec![num_rows, num_cols], camp.concat());
+    let analysis = map.analyze();
 
-    if **current_height > max_up {
-        return true;
-    }
+    let views = map
+        .heights()
+        .iter()
+        .enumerate()
+        .map(|(i, &height)| {
+            let (up, down) = analysis.distances(i, 0);
+            let (left, right) = analysis.distances(i, 1);
+            TreeView {
+                height,
+                visible: analysis.is_visible(i),
+                up,
+                down,
+                left,
+                right,
+            }
+        })
+        .collect();
 
-    let mut max_down = 0;
+    Ok(CampAnalysis {
+        num_rows,
+        num_cols,
+        views,
+    })
+}
 
-    for i in (x as usize + 1)..(camp.len()) {
-        let cmp_tree = camp.get(i).unwrap().get(y as usize).unwrap();
+/// Walks away from a tree of the given height over `trees`, nearest first.
+/// Returns the viewing distance and whether the walk left the camp
+fn look(height: u32, trees: impl Iterator<Item = u32>) -> (u32, bool) {
+    let mut distance = 0;
 
-        if cmp_tree > &max_down {
-            max_down = *cmp_tree;
+    for tree in trees {
+        distance += 1;
+        if tree >= height {
+            return (distance, false);
         }
     }
 
-    if **current_height > max_down {
-        return true;
-    }
+    (distance, true)
+}
 
-    false
+/// Looks up, down, left and right from the tree at row `x`, column `y`. Only
+/// walks that tree's row and column, so it runs in O(rows + cols)
+fn look_around(x: i32, y: i32, camp: &TreeCamp) -> Result<[(u32, bool); 4], ParseError> {
+    camp_size(camp)?;
+    let (x, y) = (x as usize, y as usize);
+    let height = camp[x][y];
+
+    Ok([
+        look(height, camp[..x].iter().rev().map(|row| row[y])),
+        look(height, camp[x + 1..].iter().map(|row| row[y])),
+        look(height, camp[x][..y].iter().rev().copied()),
+        look(height, camp[x][y + 1..].iter().copied()),
+    ])
+}
+
+/// Whether the tree at row `x`, column `y` can be seen from outside the camp.
+/// Prefer [`analyze_camp`] to check many trees
+pub fn is_tile_visible(x: i32, y: i32, camp: &TreeCamp) -> Result<bool, ParseError> {
+    Ok(look_around(x, y, camp)?
+        .iter()
+        .any(|&(_, reaches_edge)| reaches_edge))
 }
 
 // checks if the tile is an edge
@@ -65,94 +154,22 @@ pub fn is_edge(x: i32, y: i32, num_rows: usize, num_cols: usize) -> bool {
     }
 }
 
-// Do not check the edges, they wont be considered
-pub fn scenic_score_tile(x: i32, y: i32, camp: &TreeCamp) -> u32 {
-    println!("x: {}, y: {}", x, y);
-    let reference = camp.get(x as usize).unwrap().get(y as usize).unwrap();
-
-    let mut score = 1;
-
-    // Check sides
-
-    let row = camp.get(x as usize).unwrap();
-
-    let splits = row.split_at(y as usize);
-
-    // Check left
-    let mut sum_score = 0;
-
-    for i in splits.0.iter().rev() {
-        sum_score += 1;
-        if i >= reference {
-            break;
-        }
-    }
-
-    score *= sum_score;
-    println!("left Score: {}", sum_score);
-    sum_score = 0;
-
-    for i in splits.1.iter().skip(1) {
-        sum_score += 1;
-        if i >= reference {
-            break;
-        }
-    }
-
-    score *= sum_score;
-    println!("right Score: {}", sum_score);
-    sum_score = 0;
-
-    // Check up
-    for i in (0..(x as usize)).rev() {
-        let cmp_tree = camp.get(i).unwrap().get(y as usize).unwrap();
-
-        sum_score += 1;
-
-        if cmp_tree >= reference {
-            break;
-        }
-    }
-
-    score *= sum_score;
-    println!("up Score: {}", sum_score);
-    sum_score = 0;
-
-    for i in (x as usize + 1)..(camp.len()) {
-        let cmp_tree = camp.get(i).unwrap().get(y as usize).unwrap();
-
-        sum_score += 1;
-        if cmp_tree >= reference {
-            break;
-        }
-    }
-
-    score *= sum_score;
-    println!("down Score: {}", sum_score);
-    score
+/// Scenic score of the tree at row `x`, column `y`, 0 on the edges. Prefer
+/// [`analyze_camp`] to score many trees
+pub fn scenic_score_tile(x: i32, y: i32, camp: &TreeCamp) -> Result<u32, ParseError> {
+    Ok(look_around(x, y, camp)?
+        .iter()
+        .map(|&(distance, _)| distance)
+        .product())
 }
 
 pub fn find_max_scenic_score(input: &str) -> u32 {
     let camp = input_to_camp(input);
 
-    let num_rows = camp.len();
-    let num_cols = camp[0].len();
-
-    let mut scores = vec![];
-
-    for i in 0..(camp.len()) {
-        let current_row = &camp[i];
-
-        for j in 0..(current_row.len()) {
-            if is_edge(i as i32, j as i32, num_rows, num_cols) {
-                continue;
-            }
-
-            scores.push(scenic_score_tile(i as i32, j as i32, &camp));
-        }
-    }
-
-    scores.iter().max().unwrap().clone()
+    analyze_camp(&camp)
+        .expect("Camp rows should have the same length")
+        .max_scenic_score()
+        .expect("Camp has no trees")
 }
 
 pub fn input_to_camp(input: &str) -> TreeCamp {
@@ -170,38 +187,106 @@ pub fn input_to_camp(input: &str) -> TreeCamp {
 }
 
 pub fn find_how_many_visible_trees(input: &str) -> u32 {
-    let mut visible_trees = 0;
-
     let camp: TreeCamp = input_to_camp(input);
-    let num_rows = camp.len();
-    let num_cols = camp[0].len();
 
-    // Count the inner trees
-    for i in 0..(camp.len()) {
-        let current_row = &camp[i];
+    analyze_camp(&camp)
+        .expect("Camp rows should have the same length")
+        .visible_count() as u32
+}
 
-        for j in 0..(current_row.len()) {
-            if is_edge(i as i32, j as i32, num_rows, num_cols) {
-                visible_trees += 1;
-                continue;
+#[cfg(test)]
+mod tests {
+
+    use utility_2022::{get_input, is_demo_mode};
+
+    use super::*;
+
+    /// Camp of the demo input. Reads the input of the current mode, so tests
+    /// check `is_demo_mode()` first
+    pub fn demo_camp() -> TreeCamp {
+        input_to_camp(&get_input())
+    }
+
+    pub fn demo_analysis() -> CampAnalysis {
+        analyze_camp(&demo_camp()).unwrap()
+    }
+
+    pub mod analysis {
+        use super::*;
+
+        #[test]
+        fn test_demo_views() {
+            if !is_demo_mode() {
+                return;
             }
 
-            if is_tile_visible(i as i32, j as i32, &camp) {
-                visible_trees += 1;
-                continue;
+            let analysis = demo_analysis();
+
+            assert_eq!(analysis.visible_count(), 21);
+            assert_eq!(analysis.max_scenic_score(), Some(8));
+            assert_eq!(
+                *analysis.get(3, 2),
+                TreeView {
+                    height: 5,
+                    visible: true,
+                    up: 2,
+                    down: 1,
+                    left: 2,
+                    right: 2,
+                }
+            );
+            assert!(!analysis.get(1, 3).visible);
+        }
+
+        #[test]
+        fn test_edges() {
+            let camp = input_to_camp(&get_input());
+            let analysis = analyze_camp(&camp).unwrap();
+
+            for (x, y, view) in analysis.iter() {
+                let (x, y) = (x as i32, y as i32);
+                if is_edge(x, y, analysis.num_rows, analysis.num_cols) {
+                    assert!(view.visible);
+                    assert_eq!(view.scenic_score(), 0);
+                }
+                assert_eq!(is_tile_visible(x, y, &camp), Ok(view.visible));
+                assert_eq!(scenic_score_tile(x, y, &camp), Ok(view.scenic_score()));
             }
         }
-    }
 
-    visible_trees
-}
+        #[test]
+        fn test_per_tile_functions() {
+            if !is_demo_mode() {
+                return;
+            }
 
-#[cfg(test)]
-mod tests {
+            let camp = demo_camp();
+            let analysis = analyze_camp(&camp).unwrap();
 
-    use utility_2022::{get_input, is_demo_mode};
+            for (x, y, view) in analysis.iter() {
+                let (x, y) = (x as i32, y as i32);
+                assert_eq!(Ok(view.visible), is_tile_visible(x, y, &camp));
+                assert_eq!(Ok(view.scenic_score()), scenic_score_tile(x, y, &camp));
+            }
+            assert_eq!(is_tile_visible(1, 3, &camp), Ok(false));
+            assert_eq!(scenic_score_tile(1, 2, &camp), Ok(4));
+        }
 
-    use super::*;
+        #[test]
+        fn test_ragged_camp() {
+            let camp = input_to_camp("303\n2551\n653\n");
+            let error = ParseError::RowLength {
+                layer: 0,
+                row: 1,
+                expected: 3,
+                found: 4,
+            };
+
+            assert_eq!(analyze_camp(&camp).unwrap_err(), error);
+            assert_eq!(is_tile_visible(1, 1, &camp), Err(error));
+            assert!(scenic_score_tile(1, 1, &camp).is_err());
+        }
+    }
 
     pub mod part1 {
 
@@ -243,7 +328,7 @@ mod tests {
             let input = get_input();
 
             let camp = input_to_camp(&input);
-            assert_eq!(scenic_score_tile(3, 2, &camp), 8, "3,2 is incorrect");
+            assert_eq!(scenic_score_tile(3, 2, &camp), Ok(8), "3,2 is incorrect");
             assert_eq!(find_max_scenic_score(&input), 8, "Camp is incorrect");
         }
 
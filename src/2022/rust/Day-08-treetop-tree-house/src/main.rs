@@ -0,0 +1,51 @@
+use std::{env, fs};
+
+use day_08_treetop_tree_house::{analyze_camp, input_to_camp, Field};
+use utility_2022::get_input;
+
+/// Usage:
+/// - `cargo run -- top [K]` lists the K best treehouse spots (default 10)
+/// - `cargo run -- grid visibility|scenic` draws the field in the terminal
+/// - `cargo run -- pgm visibility|scenic FILE` writes the field as a PGM image
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+
+    let analysis = match analyze_camp(&input_to_camp(&get_input())) {
+        Ok(analysis) => analysis,
+        Err(e) => {
+            eprintln!("Invalid camp: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let field = |name: Option<&&str>| match name {
+        Some(&"visibility") => Field::Visibility,
+        Some(&"scenic") => Field::ScenicScore,
+        _ => {
+            eprintln!("Expected a field: visibility or scenic");
+            std::process::exit(1);
+        }
+    };
+
+    match args.first() {
+        Some(&"grid") => print!("{}", analysis.render_terminal(field(args.get(1)))),
+        Some(&"pgm") => {
+            let image = analysis.to_pgm(field(args.get(1)));
+            let path = args.get(2).expect("Expected an output file");
+            fs::write(path, image).expect("Cannot write image");
+        }
+        Some(&"top") | None => {
+            let k = args
+                .get(1)
+                .map_or(10, |k| k.parse().expect("K must be a number"));
+            for spot in analysis.top_scenic_spots(k) {
+                println!("({}, {}): {}", spot.row(), spot.col(), spot.score);
+            }
+        }
+        Some(other) => {
+            eprintln!("Unknown mode `{}`, expected top, grid or pgm", other);
+            std::process::exit(1);
+        }
+    }
+}
@@ -1,74 +1,127 @@
-#[derive(Debug)]
-struct Configuration {
-    red: u32,
-    green: u32,
-    blue: u32,
+use std::{collections::HashMap, fmt, fs, io};
+
+pub mod report;
+
+/// Cube counts keyed by colour name, e.g. `{"red": 12, "green": 13}`
+type CubeCounts = HashMap<String, u32>;
+
+/// An entry of a list of counts that is not like `3 blue`
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// The count is not a number
+    InvalidCount(String),
+    /// Nothing follows the count
+    MissingColor(String),
 }
 
-impl Configuration {
-    fn power(&self) -> u32 {
-        self.red * self.green * self.blue
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidCount(entry) => write!(f, "invalid count in `{}`", entry),
+            ParseError::MissingColor(entry) => write!(f, "missing colour in `{}`", entry),
+        }
     }
 }
 
+/// Why a bag could not be loaded from a file
 #[derive(Debug)]
-enum Colors {
-    Red(u32),
-    Green(u32),
-    Blue(u32),
+pub enum BagError {
+    Read(io::Error),
+    Parse(ParseError),
 }
 
-impl Colors {
-    fn from_text(text: &str, value: u32) -> Colors {
-        match text {
-            "red" => Colors::Red(value),
-            "green" => Colors::Green(value),
-            "blue" => Colors::Blue(value),
-            _ => panic!("Unknown color"),
+impl fmt::Display for BagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BagError::Read(e) => write!(f, "{}", e),
+            BagError::Parse(e) => write!(f, "{}", e),
         }
     }
 }
 
-#[derive(Debug)]
-struct Move {
-    red: u32,
-    green: u32,
-    blue: u32,
+/// Parses a comma separated list of counts, e.g. `3 blue, 4 red`.
+/// Newlines work as separators too, so a bag can be written one colour per line
+fn parse_counts(text: &str) -> Result<CubeCounts, ParseError> {
+    let mut counts = CubeCounts::new();
+
+    for entry in text.split([',', '\n']).filter(|x| !x.trim().is_empty()) {
+        let splits = entry.split_whitespace().collect::<Vec<&str>>();
+        let value = splits[0]
+            .parse::<u32>()
+            .map_err(|_| ParseError::InvalidCount(entry.trim().to_string()))?;
+        let color_name = splits
+            .get(1)
+            .ok_or_else(|| ParseError::MissingColor(entry.trim().to_string()))?;
+
+        *counts.entry(color_name.to_string()).or_insert(0) += value;
+    }
+
+    Ok(counts)
 }
 
-impl Move {
-    fn new(red: u32, green: u32, blue: u32) -> Move {
-        Move { red, green, blue }
+/// Cubes loaded in the bag, for any set of colours
+#[derive(Debug, Default, PartialEq)]
+pub struct Configuration {
+    cubes: CubeCounts,
+}
+
+impl Configuration {
+    /// Parses a bag like `12 red, 13 green, 14 blue`
+    pub fn from_text(text: &str) -> Result<Configuration, ParseError> {
+        Ok(Configuration {
+            cubes: parse_counts(text)?,
+        })
+    }
+
+    pub fn from_file(filename: &str) -> Result<Configuration, BagError> {
+        let text = fs::read_to_string(filename).map_err(BagError::Read)?;
+        Configuration::from_text(&text).map_err(BagError::Parse)
     }
 
+    /// Number of cubes of a colour, 0 for colours not in the bag
+    fn get(&self, color: &str) -> u32 {
+        self.cubes.get(color).copied().unwrap_or(0)
+    }
+
+    /// Product of the cubes of every colour in `bag`, so a colour of the bag
+    /// missing here makes the power 0
+    fn power(&self, bag: &Configuration) -> u32 {
+        bag.cubes.keys().map(|color| self.get(color)).product()
+    }
+}
+
+/// Cubes shown in a single reveal
+#[derive(Debug, Default)]
+struct Move {
+    cubes: CubeCounts,
+}
+
+impl Move {
     fn empty() -> Move {
-        Move::new(0, 0, 0)
+        Move::default()
     }
 
-    fn add_color(&mut self, color: Colors) {
-        match color {
-            Colors::Red(value) => self.red += value,
-            Colors::Green(value) => self.green += value,
-            Colors::Blue(value) => self.blue += value,
-        }
+    fn add_color(&mut self, color: &str, value: u32) {
+        *self.cubes.entry(color.to_string()).or_insert(0) += value;
     }
 }
 
 #[derive(Debug)]
-struct Game {
+pub struct Game {
     moves: Vec<Move>,
     id: u32,
 }
 
+/// Returns true if the move is valid, false otherwise.
 fn is_valid_move(movement: &Move, configuration: &Configuration) -> bool {
-    /// Returns true if the move is valid, false otherwise.
-    !(movement.red > configuration.red
-        || movement.green > configuration.green
-        || movement.blue > configuration.blue)
+    movement
+        .cubes
+        .iter()
+        .all(|(color, &value)| value <= configuration.get(color))
 }
 
+/// Returns true if the game is valid, false otherwise.
 fn is_valid_game(game: &Game, configuration: &Configuration) -> bool {
-    /// Returns true if the game is valid, false otherwise.
     game.moves
         .iter()
         .all(|move_| is_valid_move(move_, configuration))
@@ -76,7 +129,7 @@ fn is_valid_game(game: &Game, configuration: &Configuration) -> bool {
 
 /// Parse move given a game:
 /// e.g: Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-fn parse_line(line: &str) -> Game {
+pub fn parse_line(line: &str) -> Game {
     // Split the line into the game id and the moves
     let mut split_game = line.split(":");
     let game_id_string = split_game.next().unwrap();
@@ -85,8 +138,7 @@ fn parse_line(line: &str) -> Game {
     // Get the game id
     let id = game_id_string
         .split_whitespace()
-        .skip(1)
-        .next()
+        .nth(1)
         .unwrap()
         .parse::<u32>()
         .expect("Could not parse game id");
@@ -94,21 +146,9 @@ fn parse_line(line: &str) -> Game {
     // Parse all the moves
     let moves = all_moves_str
         .map(|moves| {
-            let results = moves
-                .split(",")
-                .map(|x| {
-                    let splits = x.split_whitespace().collect::<Vec<&str>>();
-                    let color_name = splits[1];
-                    let value = splits[0].parse::<u32>().expect("Could not parse value");
-
-                    let c = Colors::from_text(color_name, value);
-                    c
-                })
-                .collect::<Vec<Colors>>();
-
             let mut move_ = Move::empty();
-            for color in results {
-                move_.add_color(color);
+            for (color, value) in parse_counts(moves).expect("Could not parse reveal") {
+                move_.add_color(&color, value);
             }
             move_
         })
@@ -117,17 +157,18 @@ fn parse_line(line: &str) -> Game {
     Game { moves, id }
 }
 
+/// Smallest bag that makes the game possible, with every colour the game shows
 fn find_minimum_configuration(game: &Game) -> Configuration {
-    let mut minimum_configuration = Configuration {
-        red: 0,
-        green: 0,
-        blue: 0,
-    };
+    let mut minimum_configuration = Configuration::default();
 
     for move_ in &game.moves {
-        minimum_configuration.red = minimum_configuration.red.max(move_.red);
-        minimum_configuration.green = minimum_configuration.green.max(move_.green);
-        minimum_configuration.blue = minimum_configuration.blue.max(move_.blue);
+        for (color, &value) in &move_.cubes {
+            let minimum = minimum_configuration
+                .cubes
+                .entry(color.clone())
+                .or_insert(0);
+            *minimum = (*minimum).max(value);
+        }
     }
 
     minimum_configuration
@@ -138,11 +179,74 @@ mod tests {
     use super::*;
     use utility_2022::{get_input, is_demo_mode};
 
-    const initial_config: Configuration = Configuration {
-        red: 12,
-        green: 13,
-        blue: 14,
-    };
+    /// The bag from the puzzle, kept in bag.txt
+    pub fn initial_config() -> Configuration {
+        Configuration::from_file("bag.txt").expect("Cannot read bag.txt")
+    }
+
+    pub mod colors {
+        use super::*;
+
+        #[test]
+        fn test_bag_from_file() {
+            let bag = initial_config();
+
+            assert_eq!(bag.get("red"), 12);
+            assert_eq!(bag.get("green"), 13);
+            assert_eq!(bag.get("blue"), 14);
+            assert_eq!(bag.get("yellow"), 0);
+        }
+
+        #[test]
+        fn test_any_colors() {
+            let bag = Configuration::from_text("2 yellow\n5 purple\n").unwrap();
+
+            let valid = parse_line("Game 1: 2 yellow, 1 purple; 5 purple");
+            let invalid = parse_line("Game 2: 1 yellow; 1 red");
+
+            assert!(is_valid_game(&valid, &bag));
+            assert!(!is_valid_game(&invalid, &bag));
+
+            let minimum = find_minimum_configuration(&valid);
+            assert_eq!(
+                minimum,
+                Configuration::from_text("2 yellow, 5 purple").unwrap()
+            );
+            assert_eq!(minimum.power(&bag), 10);
+        }
+
+        #[test]
+        fn test_power_missing_color() {
+            let game = parse_line("Game 1: 3 red, 2 blue; 1 yellow");
+            let minimum = find_minimum_configuration(&game);
+
+            assert_eq!(minimum.power(&initial_config()), 0);
+            assert_eq!(
+                minimum.power(&Configuration::from_text("1 red, 1 blue").unwrap()),
+                6
+            );
+        }
+
+        #[test]
+        fn test_bag_file_missing() {
+            assert!(matches!(
+                Configuration::from_file("no-such-bag.txt"),
+                Err(BagError::Read(_))
+            ));
+        }
+
+        #[test]
+        fn test_malformed_bag() {
+            assert_eq!(
+                Configuration::from_text("12 red, thirteen green"),
+                Err(ParseError::InvalidCount("thirteen green".to_string()))
+            );
+            assert_eq!(
+                Configuration::from_text("12 red, 13"),
+                Err(ParseError::MissingColor("13".to_string()))
+            );
+        }
+    }
 
     pub mod part1 {
 
@@ -159,7 +263,7 @@ mod tests {
             let mut sum = 0;
             for line in input.lines() {
                 let game = parse_line(line);
-                let is_valid_game = is_valid_game(&game, &initial_config);
+                let is_valid_game = is_valid_game(&game, &initial_config());
 
                 if !is_valid_game {
                     continue;
@@ -181,7 +285,7 @@ mod tests {
             let mut sum = 0;
             for line in input.lines() {
                 let game = parse_line(line);
-                let is_valid_game = is_valid_game(&game, &initial_config);
+                let is_valid_game = is_valid_game(&game, &initial_config());
 
                 if !is_valid_game {
                     continue;
@@ -209,9 +313,7 @@ mod tests {
             for line in input.lines() {
                 let game = parse_line(line);
                 let min_config = find_minimum_configuration(&game);
-                println!("Game: {}, min_config: {:?}", game.id, min_config);
-                println!("Power: {}", min_config.power());
-                sum += min_config.power();
+                sum += min_config.power(&initial_config());
             }
 
             assert_eq!(sum, 2286);
@@ -229,7 +331,7 @@ mod tests {
             for line in input.lines() {
                 let game = parse_line(line);
                 let min_config = find_minimum_configuration(&game);
-                sum += min_config.power();
+                sum += min_config.power(&initial_config());
             }
 
             println!("Answer pt2: {}", sum);
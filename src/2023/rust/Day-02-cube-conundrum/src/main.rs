@@ -0,0 +1,39 @@
+use std::env;
+
+use day_02_cube_conundrum::{parse_line, report::build_report, Configuration, Game};
+use utility_2022::get_input;
+
+/// Usage: `cargo run -- [text|json] [BAG]`
+///
+/// Checks every game of the puzzle input against the bag described in BAG
+/// (`bag.txt` by default) and prints the report. Defaults to `text`
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let format = args.first().map_or("text", |f| f.as_str());
+    let bag_file = args.get(1).map_or("bag.txt", |b| b.as_str());
+
+    let bag = match Configuration::from_file(bag_file) {
+        Ok(bag) => bag,
+        Err(e) => {
+            eprintln!("Invalid bag {}: {}", bag_file, e);
+            std::process::exit(1);
+        }
+    };
+
+    let input = get_input();
+    let games = input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_line)
+        .collect::<Vec<Game>>();
+    let report = build_report(&games, &bag);
+
+    match format {
+        "text" => println!("{}", report.to_text()),
+        "json" => println!("{}", report.to_json()),
+        other => {
+            eprintln!("Unknown format `{}`, expected text or json", other);
+            std::process::exit(1);
+        }
+    }
+}
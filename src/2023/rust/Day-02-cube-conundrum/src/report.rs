@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{find_minimum_configuration, is_valid_game, Configuration, CubeCounts, Game};
+
+/// A colour in a reveal that shows more cubes than the bag holds
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Violation {
+    /// 1-based position of the reveal in the game
+    pub reveal: usize,
+    pub color: String,
+    pub shown: u32,
+    pub limit: u32,
+    pub excess: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GameReport {
+    pub id: u32,
+    pub feasible: bool,
+    pub violations: Vec<Violation>,
+    pub minimum_bag: BTreeMap<String, u32>,
+    pub power: u32,
+}
+
+/// How many cubes of one colour were drawn across every reveal
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ColorStats {
+    /// Reveals that showed the colour
+    pub reveals: u32,
+    pub total: u32,
+    pub max: u32,
+    /// Number of reveals that showed each count of cubes
+    pub distribution: BTreeMap<u32, u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub bag: BTreeMap<String, u32>,
+    pub games: Vec<GameReport>,
+    pub feasible_id_sum: u32,
+    pub power_sum: u32,
+    pub colors: BTreeMap<String, ColorStats>,
+}
+
+fn sorted(counts: &CubeCounts) -> BTreeMap<String, u32> {
+    counts.iter().map(|(c, &v)| (c.clone(), v)).collect()
+}
+
+fn format_counts(counts: &BTreeMap<String, u32>) -> String {
+    counts
+        .iter()
+        .map(|(color, value)| format!("{} {}", value, color))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn game_report(game: &Game, bag: &Configuration) -> GameReport {
+    let mut violations = vec![];
+
+    for (index, move_) in game.moves.iter().enumerate() {
+        for (color, shown) in sorted(&move_.cubes) {
+            let limit = bag.get(&color);
+            if shown > limit {
+                violations.push(Violation {
+                    reveal: index + 1,
+                    color,
+                    shown,
+                    limit,
+                    excess: shown - limit,
+                });
+            }
+        }
+    }
+
+    let minimum = find_minimum_configuration(game);
+
+    GameReport {
+        id: game.id,
+        feasible: is_valid_game(game, bag),
+        violations,
+        minimum_bag: sorted(&minimum.cubes),
+        power: minimum.power(bag),
+    }
+}
+
+/// Checks every game against the bag and collects the draws per colour
+pub fn build_report(games: &[Game], bag: &Configuration) -> Report {
+    let reports = games
+        .iter()
+        .map(|game| game_report(game, bag))
+        .collect::<Vec<GameReport>>();
+
+    let mut colors: BTreeMap<String, ColorStats> = BTreeMap::new();
+    for (color, &value) in games.iter().flat_map(|g| &g.moves).flat_map(|m| &m.cubes) {
+        let stats = colors.entry(color.clone()).or_default();
+        stats.reveals += 1;
+        stats.total += value;
+        stats.max = stats.max.max(value);
+        *stats.distribution.entry(value).or_insert(0) += 1;
+    }
+
+    Report {
+        bag: sorted(&bag.cubes),
+        feasible_id_sum: reports.iter().filter(|r| r.feasible).map(|r| r.id).sum(),
+        power_sum: reports.iter().map(|r| r.power).sum(),
+        games: reports,
+        colors,
+    }
+}
+
+impl Report {
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![format!("Bag: {}", format_counts(&self.bag))];
+
+        for game in &self.games {
+            lines.push(format!(
+                "Game {}: {}, minimum bag {} (power {})",
+                game.id,
+                if game.feasible {
+                    "possible"
+                } else {
+                    "impossible"
+                },
+                format_counts(&game.minimum_bag),
+                game.power
+            ));
+            for v in &game.violations {
+                lines.push(format!(
+                    "  reveal {}: {} {} > {} (+{})",
+                    v.reveal, v.shown, v.color, v.limit, v.excess
+                ));
+            }
+        }
+
+        lines.push(format!("Possible games id sum: {}", self.feasible_id_sum));
+        lines.push(format!("Power sum: {}", self.power_sum));
+        lines.push("Draws per colour:".to_string());
+        for (color, stats) in &self.colors {
+            let distribution = stats
+                .distribution
+                .iter()
+                .map(|(value, times)| format!("{}x{}", value, times))
+                .collect::<Vec<String>>()
+                .join(" ");
+            lines.push(format!(
+                "  {}: {} reveals, {} cubes, max {}, counts {}",
+                color, stats.reveals, stats.total, stats.max, distribution
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Report should always serialize")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utility_2022::{get_input, is_demo_mode};
+
+    use crate::{parse_line, tests::initial_config};
+
+    use super::*;
+
+    fn demo_report() -> Report {
+        let games = get_input().lines().map(parse_line).collect::<Vec<Game>>();
+        build_report(&games, &initial_config())
+    }
+
+    #[test]
+    fn test_report() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        let report = demo_report();
+
+        assert_eq!(report.feasible_id_sum, 8);
+        assert_eq!(report.power_sum, 2286);
+
+        let game_3 = &report.games[2];
+        assert!(!game_3.feasible);
+        assert_eq!(
+            game_3.violations,
+            vec![Violation {
+                reveal: 1,
+                color: "red".to_string(),
+                shown: 20,
+                limit: 12,
+                excess: 8,
+            }]
+        );
+        assert_eq!(game_3.power, 1560);
+
+        let blue = &report.colors["blue"];
+        assert_eq!(blue.max, 15);
+        assert_eq!(blue.reveals, blue.distribution.values().sum::<u32>());
+    }
+
+    #[test]
+    fn test_text_and_json() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        let report = demo_report();
+
+        let text = report.to_text();
+        assert!(
+            text.contains("Game 3: impossible, minimum bag 6 blue, 13 green, 20 red (power 1560)")
+        );
+        assert!(text.contains("  reveal 1: 20 red > 12 (+8)"));
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["power_sum"], 2286);
+        assert_eq!(json["games"][2]["violations"][0]["excess"], 8);
+    }
+}
@@ -1,142 +1,254 @@
-use std::collections::HashSet;
+#![allow(non_snake_case)]
+
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Coords {
-    x: usize,
-    y: usize,
+pub struct Coords {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Coords {
+    /// The up to 8 cells around these coords
+    fn neighbours(&self) -> impl Iterator<Item = Coords> + '_ {
+        (self.y.saturating_sub(1)..=self.y + 1)
+            .flat_map(move |y| {
+                (self.x.saturating_sub(1)..=self.x + 1).map(move |x| Coords { x, y })
+            })
+            .filter(move |c| c != self)
+    }
 }
 
 #[derive(Debug, Clone)]
-struct Number {
-    coords: Vec<Coords>,
-    number: Option<String>,
-    is_valid: bool,
+pub struct Number {
+    pub coords: Vec<Coords>,
+    pub value: u32,
 }
 
 impl Number {
     fn empty() -> Self {
         Self {
             coords: Vec::new(),
-            number: None,
-            is_valid: false,
+            value: 0,
         }
     }
 
-    fn add_digit(&mut self, digit: char) {
-        if self.number.is_none() {
-            self.number = Some(String::new());
+    fn add_digit(&mut self, digit: char, coords: Coords) {
+        self.value = self.value * 10 + digit.to_digit(10).expect("Not a digit");
+        self.coords.push(coords);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Symbol {
+    pub symbol: char,
+    pub coords: Coords,
+}
+
+/// Which part numbers touch which symbols. Ids are indices into
+/// `SchematicMap::numbers` and `SchematicMap::symbols`
+#[derive(Debug, Default)]
+struct AdjacencyGraph {
+    numbers_of_symbol: Vec<Vec<usize>>,
+    symbols_of_number: Vec<Vec<usize>>,
+}
+
+/// Symbols a rule applies to
+#[derive(Debug, Clone)]
+pub enum SymbolClass {
+    Any,
+    OneOf(Vec<char>),
+}
+
+impl SymbolClass {
+    pub fn matches(&self, symbol: char) -> bool {
+        match self {
+            SymbolClass::Any => true,
+            SymbolClass::OneOf(symbols) => symbols.contains(&symbol),
         }
+    }
+}
 
-        self.number.as_mut().unwrap().push(digit);
+/// How many numbers a symbol must touch for a rule to apply
+#[derive(Debug, Clone, Copy)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+    AtMost(usize),
+}
+
+impl Arity {
+    pub fn matches(&self, count: usize) -> bool {
+        match *self {
+            Arity::Exactly(n) => count == n,
+            Arity::AtLeast(n) => count >= n,
+            Arity::AtMost(n) => count <= n,
+        }
     }
+}
 
-    fn add_coords(&mut self, coords: Coords) {
-        self.coords.push(coords);
+/// How the numbers around a matching symbol are turned into a value
+#[derive(Debug, Clone, Copy)]
+pub enum Aggregation {
+    Product,
+    Sum,
+    Max,
+}
+
+impl Aggregation {
+    pub fn apply(&self, values: impl Iterator<Item = u32>) -> u32 {
+        match self {
+            Aggregation::Product => values.product(),
+            Aggregation::Sum => values.sum(),
+            Aggregation::Max => values.max().unwrap_or(0),
+        }
     }
+}
+
+/// Declares which symbols count as gears and how their numbers are combined.
+/// The answer is the sum of the values of every matching symbol
+#[derive(Debug, Clone)]
+pub struct GearRule {
+    pub symbols: SymbolClass,
+    pub arity: Arity,
+    pub aggregation: Aggregation,
+    /// A number touching several matching symbols only counts for the first one
+    pub each_number_once: bool,
+}
 
-    fn set_valid(&mut self) {
-        self.is_valid = true;
+impl GearRule {
+    /// Part 1: every number next to any symbol, counted once
+    pub fn part_numbers() -> GearRule {
+        GearRule {
+            symbols: SymbolClass::Any,
+            arity: Arity::AtLeast(1),
+            aggregation: Aggregation::Sum,
+            each_number_once: true,
+        }
+    }
+
+    /// Part 2: the product of the two numbers around every `*` with exactly two
+    pub fn gear_ratios() -> GearRule {
+        GearRule {
+            symbols: SymbolClass::OneOf(vec!['*']),
+            arity: Arity::Exactly(2),
+            aggregation: Aggregation::Product,
+            each_number_once: false,
+        }
     }
 }
 
 #[derive(Debug)]
-struct SchematicMap {
-    symbols: HashSet<Coords>,
+pub struct SchematicMap {
     numbers: Vec<Number>,
-    gears: Vec<Coords>,
+    symbols: Vec<Symbol>,
+    /// Id of the number covering each cell, for O(1) lookups
+    number_at: HashMap<Coords, usize>,
+    graph: AdjacencyGraph,
 }
 
 impl SchematicMap {
-    /// Check if they have a symbol in their surroundings
-    fn validate_numbers(&mut self) -> u32 {
-        let mut sum = 0;
-        for number in self.numbers.iter_mut() {
-            if number.is_valid {
-                sum += number.number.as_ref().unwrap().parse::<u32>().unwrap();
-                continue;
-            }
-
-            // Check if the number has a symbol in its surroundings
-            let mut has_symbol = false;
-            for coords in number.coords.iter() {
-                for x in (coords.x.saturating_sub(1))..=(coords.x.saturating_add(1)) {
-                    for y in (coords.y.saturating_sub(1))..=(coords.y.saturating_add(1)) {
-                        if self.symbols.contains(&Coords { x, y }) {
-                            has_symbol = true;
-                            break;
-                        }
-                    }
-
-                    if has_symbol {
-                        break;
-                    }
+    /// Links every symbol with the numbers in its surroundings
+    fn build_graph(&mut self) {
+        let mut graph = AdjacencyGraph {
+            numbers_of_symbol: vec![vec![]; self.symbols.len()],
+            symbols_of_number: vec![vec![]; self.numbers.len()],
+        };
+
+        for (symbol_id, symbol) in self.symbols.iter().enumerate() {
+            for coords in symbol.coords.neighbours() {
+                let Some(&number_id) = self.number_at.get(&coords) else {
+                    continue;
+                };
+
+                let numbers = &mut graph.numbers_of_symbol[symbol_id];
+                if !numbers.contains(&number_id) {
+                    numbers.push(number_id);
+                    graph.symbols_of_number[number_id].push(symbol_id);
                 }
             }
-
-            if has_symbol {
-                number.set_valid();
-                sum += number.number.as_ref().unwrap().parse::<u32>().unwrap();
-            }
         }
-        sum
-    }
 
-    fn find_gear_ratio_sum(&self) -> u32 {
-        let mut gear_ratio_sum = 0;
+        self.graph = graph;
+    }
 
-        // Check for every gear if it has 2 numbers in its surroundings
+    /// Every number, indexed by its id
+    pub fn numbers(&self) -> &[Number] {
+        &self.numbers
+    }
 
-        for g_coords in self.gears.iter() {
-            let mut coords_to_check: Vec<Coords> = Vec::new();
+    /// Every symbol, indexed by its id
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
 
-            // Find all possible empty spaces or numbers in the surroundings
-            for x in (g_coords.x.saturating_sub(1))..=(g_coords.x.saturating_add(1)) {
-                for y in (g_coords.y.saturating_sub(1))..=(g_coords.y.saturating_add(1)) {
-                    // Skip the gear itself
-                    if x == g_coords.x && y == g_coords.y {
-                        continue;
-                    }
+    /// The number covering a cell, if any
+    pub fn number_at(&self, coords: Coords) -> Option<&Number> {
+        self.number_at.get(&coords).map(|&id| &self.numbers[id])
+    }
 
-                    // Skip any symbol
-                    if self.symbols.contains(&Coords { x, y }) {
-                        continue;
-                    }
+    /// Ids of the numbers around a symbol
+    pub fn numbers_of_symbol(&self, symbol_id: usize) -> &[usize] {
+        &self.graph.numbers_of_symbol[symbol_id]
+    }
 
-                    // Possible empty space or number
-                    coords_to_check.push(Coords { x, y });
-                }
-            }
+    /// Ids of the symbols around a number
+    pub fn symbols_of_number(&self, number_id: usize) -> &[usize] {
+        &self.graph.symbols_of_number[number_id]
+    }
 
-            // Check with every number if its in its surroudings
-            let mut numbers_in_surroundings = 0;
-            let mut gear_ratio = 1;
-            for number in self.numbers.iter() {
-                let mut gear_has_this_number = false;
-
-                for coords in number.coords.iter() {
-                    if coords_to_check.contains(coords) {
-                        gear_has_this_number = true;
-                        break;
-                    }
-                }
+    /// Symbols matching the rule, with the value of their numbers
+    pub fn matching_symbols(&self, rule: &GearRule) -> Vec<(usize, u32)> {
+        let mut counted = vec![false; self.numbers.len()];
+        let mut matches = vec![];
 
-                // Add it to the sum if the gear has this number
-                if gear_has_this_number {
-                    numbers_in_surroundings += 1;
-                    gear_ratio *= number.number.as_ref().unwrap().parse::<u32>().unwrap();
-                }
+        for (id, symbol) in self.symbols.iter().enumerate() {
+            let numbers = self.numbers_of_symbol(id);
+            if !rule.symbols.matches(symbol.symbol) || !rule.arity.matches(numbers.len()) {
+                continue;
+            }
 
-                if numbers_in_surroundings > 2 {
-                    break;
-                }
+            let values = numbers
+                .iter()
+                .filter(|&&n| !rule.each_number_once || !counted[n])
+                .map(|&n| self.numbers[n].value)
+                .collect::<Vec<u32>>();
+            for &n in numbers {
+                counted[n] = true;
             }
 
-            if numbers_in_surroundings == 2 {
-                gear_ratio_sum += gear_ratio;
+            if !values.is_empty() {
+                matches.push((id, rule.aggregation.apply(values.into_iter())));
             }
         }
 
-        gear_ratio_sum
+        matches
+    }
+
+    /// Sum of the values of every symbol matching the rule
+    pub fn evaluate(&self, rule: &GearRule) -> u32 {
+        self.matching_symbols(rule)
+            .iter()
+            .map(|(_, value)| value)
+            .sum()
+    }
+
+    /// Numbers that do not touch any symbol
+    pub fn lonely_numbers(&self) -> Vec<&Number> {
+        (0..self.numbers.len())
+            .filter(|&id| self.symbols_of_number(id).is_empty())
+            .map(|id| &self.numbers[id])
+            .collect()
+    }
+
+    /// Sums the numbers that have a symbol in their surroundings
+    pub fn validate_numbers(&self) -> u32 {
+        self.evaluate(&GearRule::part_numbers())
+    }
+
+    /// Sums the products of the two numbers around every `*` that touches exactly two
+    pub fn find_gear_ratio_sum(&self) -> u32 {
+        self.evaluate(&GearRule::gear_ratios())
     }
 }
 
@@ -153,50 +265,55 @@ impl SchematicMap {
 /// ......755.
 /// ...$.*....
 /// .664.598..
-fn parse_input(input: &str) -> SchematicMap {
-    let mut symbols: HashSet<Coords> = HashSet::new();
+pub fn parse_input(input: &str) -> SchematicMap {
+    let mut symbols: Vec<Symbol> = Vec::new();
     let mut numbers: Vec<Number> = Vec::new();
-    let mut gears: Vec<Coords> = Vec::new();
+    let mut number_at: HashMap<Coords, usize> = HashMap::new();
 
     let mut number: Option<Number> = None;
 
+    // Stores the number being read, if any, and indexes its cells
+    let mut flush = |number: &mut Option<Number>, numbers: &mut Vec<Number>| {
+        if let Some(number) = number.take() {
+            for coords in number.coords.iter() {
+                number_at.insert(*coords, numbers.len());
+            }
+            numbers.push(number);
+        }
+    };
+
     for (y, line) in input.lines().enumerate() {
         for (x, symbol) in line.chars().enumerate() {
             match symbol {
                 // Skip empty spaces
-                '.' => {
-                    if let Some(number) = number.take() {
-                        numbers.push(number.clone());
-                    }
-                }
+                '.' => flush(&mut number, &mut numbers),
                 // Add digit to number
-                '0'..='9' => {
-                    if number.is_none() {
-                        number = Some(Number::empty());
-                    }
-                    let number = number.as_mut().unwrap();
-                    number.add_digit(symbol);
-                    number.add_coords(Coords { x, y });
-                }
-                // Mark a symbol in the hash set
+                '0'..='9' => number
+                    .get_or_insert_with(Number::empty)
+                    .add_digit(symbol, Coords { x, y }),
+                // Keep track of the symbol
                 symbol => {
-                    if let Some(number) = number.take() {
-                        numbers.push(number.clone());
-                    }
-                    if symbol == '*' {
-                        gears.push(Coords { x, y });
-                    }
-                    symbols.insert(Coords { x, y });
+                    flush(&mut number, &mut numbers);
+                    symbols.push(Symbol {
+                        symbol,
+                        coords: Coords { x, y },
+                    });
                 }
             }
         }
+
+        // Numbers never continue on the next line
+        flush(&mut number, &mut numbers);
     }
 
-    SchematicMap {
-        symbols,
+    let mut map = SchematicMap {
         numbers,
-        gears,
-    }
+        symbols,
+        number_at,
+        graph: AdjacencyGraph::default(),
+    };
+    map.build_graph();
+    map
 }
 
 #[cfg(test)]
@@ -204,6 +321,85 @@ mod tests {
     use super::*;
     use utility_2022::{get_input, is_demo_mode};
 
+    pub mod schematic {
+        use super::*;
+
+        #[test]
+        fn test_numbers_end_at_line_end() {
+            let map = parse_input("..12\n34*.\n....\n");
+
+            let values = map.numbers.iter().map(|n| n.value).collect::<Vec<u32>>();
+            assert_eq!(values, vec![12, 34]);
+            assert_eq!(map.validate_numbers(), 46);
+        }
+
+        #[test]
+        fn test_adjacency_graph() {
+            if !is_demo_mode() {
+                return;
+            }
+
+            let map = parse_input(&get_input());
+
+            assert_eq!(map.number_at(Coords { x: 1, y: 0 }).unwrap().value, 467);
+            assert!(map.number_at(Coords { x: 3, y: 0 }).is_none());
+
+            // The `*` on the second line touches 467 and 35
+            let star = map
+                .symbols
+                .iter()
+                .position(|s| s.coords == Coords { x: 3, y: 1 })
+                .unwrap();
+            let values = map
+                .numbers_of_symbol(star)
+                .iter()
+                .map(|&n| map.numbers[n].value)
+                .collect::<Vec<u32>>();
+            assert_eq!(values, vec![467, 35]);
+
+            // 114 and 58 touch no symbol
+            let lonely = map
+                .lonely_numbers()
+                .iter()
+                .map(|n| n.value)
+                .collect::<Vec<u32>>();
+            assert_eq!(lonely, vec![114, 58]);
+        }
+
+        #[test]
+        fn test_custom_rules() {
+            let map = parse_input("2.3\n.#.\n4.5\n1*.\n");
+
+            let hash_with_three = GearRule {
+                symbols: SymbolClass::OneOf(vec!['#']),
+                arity: Arity::AtLeast(3),
+                aggregation: Aggregation::Max,
+                each_number_once: false,
+            };
+            assert_eq!(map.evaluate(&hash_with_three), 5);
+
+            let any_with_one = GearRule {
+                symbols: SymbolClass::Any,
+                arity: Arity::Exactly(1),
+                aggregation: Aggregation::Sum,
+                each_number_once: false,
+            };
+            assert!(map.matching_symbols(&any_with_one).is_empty());
+
+            let stars = GearRule {
+                symbols: SymbolClass::OneOf(vec!['*']),
+                arity: Arity::AtMost(3),
+                aggregation: Aggregation::Product,
+                each_number_once: false,
+            };
+            // The `*` touches 4, 5 and 1
+            assert_eq!(map.evaluate(&stars), 20);
+
+            // 4 and 5 touch both symbols but count once
+            assert_eq!(map.evaluate(&GearRule::part_numbers()), 2 + 3 + 4 + 5 + 1);
+        }
+    }
+
     pub mod part1 {
 
         use super::*;
@@ -215,15 +411,10 @@ mod tests {
             }
 
             let input = get_input();
-            let mut map = parse_input(&input);
+            let map = parse_input(&input);
 
             let sum = map.validate_numbers();
 
-            // println!("Invalid numbers");
-            // for number in map.numbers.iter().filter(|n| !n.is_valid) {
-            //     println!("Number: {:?}", number.number);
-            // }
-
             assert_eq!(sum, 4361);
         }
 
@@ -234,7 +425,7 @@ mod tests {
             }
 
             let input = get_input();
-            let mut map = parse_input(&input);
+            let map = parse_input(&input);
             let sum = map.validate_numbers();
 
             println!("Answer pt1: {}", sum);
@@ -252,7 +443,7 @@ mod tests {
             }
 
             let input = get_input();
-            let mut map = parse_input(&input);
+            let map = parse_input(&input);
             let gear_ratio_sum = map.find_gear_ratio_sum();
 
             assert_eq!(gear_ratio_sum, 467835);
@@ -265,7 +456,7 @@ mod tests {
             }
 
             let input = get_input();
-            let mut map = parse_input(&input);
+            let map = parse_input(&input);
             let gear_ratio_sum = map.find_gear_ratio_sum();
 
             println!("Answer pt2: {}", gear_ratio_sum);
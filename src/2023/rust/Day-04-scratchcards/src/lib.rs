@@ -1,56 +1,79 @@
+#![allow(non_snake_case)]
+
 use std::collections::HashSet;
 
-type ScratchNumbers = HashSet<u8>;
+type ScratchNumbers = HashSet<u32>;
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// The line has no `Card N:` header or no `|` between the two lists
+    Malformed {
+        line: usize,
+    },
+    InvalidNumber {
+        line: usize,
+        token: String,
+    },
+}
 
-trait ScratchNumberFromNumbersSplit {
-    fn from_numbers_split(numbers_split: &str) -> Self;
+trait ScratchNumberFromNumbersSplit: Sized {
+    fn from_numbers_split(numbers_split: &str, line: usize) -> Result<Self, ParseError>;
 }
 
 impl ScratchNumberFromNumbersSplit for ScratchNumbers {
-    fn from_numbers_split(numbers_split: &str) -> Self {
+    fn from_numbers_split(numbers_split: &str, line: usize) -> Result<Self, ParseError> {
         numbers_split
-            .split(" ")
-            .filter_map(|number| number.parse::<u8>().ok())
-            .collect::<ScratchNumbers>()
+            .split_whitespace()
+            .map(|number| {
+                number
+                    .parse::<u32>()
+                    .map_err(|_| ParseError::InvalidNumber {
+                        line,
+                        token: number.to_string(),
+                    })
+            })
+            .collect()
     }
 }
 
 #[derive(Debug)]
-struct ScratchCard {
-    winning_numbers: ScratchNumbers,
-    scratch_numbers: ScratchNumbers,
+pub struct ScratchCard {
+    pub id: u32,
+    pub winning_numbers: ScratchNumbers,
+    pub scratch_numbers: ScratchNumbers,
+    /// How many scratched numbers are winning numbers
+    pub matches: usize,
 }
 
 impl ScratchCard {
-    fn new(winning_numbers: ScratchNumbers, scratch_numbers: ScratchNumbers) -> Self {
+    pub fn new(id: u32, winning_numbers: ScratchNumbers, scratch_numbers: ScratchNumbers) -> Self {
+        let matches = winning_numbers.intersection(&scratch_numbers).count();
+
         Self {
+            id,
             winning_numbers,
             scratch_numbers,
+            matches,
         }
     }
 
-    fn points(&self) -> u32 {
-        let matches = self
-            .winning_numbers
-            .intersection(&self.scratch_numbers)
-            .count() as u32;
-
-        if matches == 0 {
+    pub fn points(&self) -> u32 {
+        if self.matches == 0 {
             0
         } else {
-            2_u32.pow(matches - 1)
+            2_u32.pow(self.matches as u32 - 1)
         }
     }
 }
 
 #[derive(Debug)]
-struct ScratchCardList {
-    cards: Vec<ScratchCard>,
-    copies: Vec<usize>,
+pub struct ScratchCardList {
+    pub cards: Vec<ScratchCard>,
+    pub copies: Vec<usize>,
 }
 
 impl ScratchCardList {
-    fn new(cards: Vec<ScratchCard>) -> Self {
+    pub fn new(cards: Vec<ScratchCard>) -> Self {
         Self {
             copies: vec![1; cards.len()],
             cards,
@@ -58,46 +81,79 @@ impl ScratchCardList {
     }
 }
 
-fn parse_input(input: &str) -> Vec<ScratchCard> {
+/// How a card ended up with its copies
+#[derive(Debug, PartialEq)]
+pub struct CascadeEntry {
+    pub card: u32,
+    /// Original card included
+    pub copies: usize,
+    /// Earlier cards that won copies of this one, with how many each gave
+    pub won_from: Vec<(u32, usize)>,
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<ScratchCard>, ParseError> {
     input
         .lines()
-        .map(|card| {
-            let numbers_part = card.split(":").skip(1).next().unwrap().trim();
-            let mut numbers_splits = numbers_part.split("|").map(|part| part.trim());
-
-            let winning_part = numbers_splits.next().unwrap();
-            let selected_numbers = numbers_splits.next().unwrap();
-
-            let winning_numbers = ScratchNumbers::from_numbers_split(winning_part);
-            let scratch_numbers = ScratchNumbers::from_numbers_split(selected_numbers);
-
-            ScratchCard::new(winning_numbers, scratch_numbers)
+        .enumerate()
+        .filter(|(_, card)| !card.trim().is_empty())
+        .map(|(index, card)| {
+            let line = index + 1;
+            let malformed = || ParseError::Malformed { line };
+
+            let (header, numbers_part) = card.split_once(':').ok_or_else(malformed)?;
+            let (winning_part, selected_numbers) =
+                numbers_part.split_once('|').ok_or_else(malformed)?;
+
+            let id = header
+                .split_whitespace()
+                .nth(1)
+                .and_then(|id| id.parse::<u32>().ok())
+                .ok_or_else(malformed)?;
+
+            let winning_numbers = ScratchNumbers::from_numbers_split(winning_part, line)?;
+            let scratch_numbers = ScratchNumbers::from_numbers_split(selected_numbers, line)?;
+
+            Ok(ScratchCard::new(id, winning_numbers, scratch_numbers))
         })
-        .collect::<Vec<ScratchCard>>()
+        .collect()
 }
 
-fn parse_scratchcard_copies(scratchcard_list: &ScratchCardList) -> usize {
-    let mut copies = scratchcard_list.copies.clone();
-
-    for (card_idx, cards) in scratchcard_list.cards.iter().enumerate() {
-        let points = cards.points();
-        if points == 0 {
-            continue;
-        }
+pub fn total_points(cards: &[ScratchCard]) -> u32 {
+    cards.iter().map(|card| card.points()).sum()
+}
 
-        let matches = (points.ilog2() + 1) as usize;
-        let current_copy = copies.get(card_idx).unwrap().clone();
+/// Plays the cards in order: each card with N matches wins one copy of each
+/// of the next N cards per copy of itself
+pub fn cascade(scratchcard_list: &ScratchCardList) -> Vec<CascadeEntry> {
+    let mut entries = scratchcard_list
+        .cards
+        .iter()
+        .zip(&scratchcard_list.copies)
+        .map(|(card, &copies)| CascadeEntry {
+            card: card.id,
+            copies,
+            won_from: vec![],
+        })
+        .collect::<Vec<CascadeEntry>>();
 
-        for idx in card_idx + 1..card_idx + 1 + matches {
-            let no_copy = copies.get_mut(idx);
+    for (card_idx, card) in scratchcard_list.cards.iter().enumerate() {
+        let current_copy = entries[card_idx].copies;
+        let last = (card_idx + card.matches).min(entries.len() - 1);
 
-            if let Some(copy) = no_copy {
-                *copy += 1 * current_copy;
-            }
+        for entry in &mut entries[card_idx + 1..=last] {
+            entry.copies += current_copy;
+            entry.won_from.push((card.id, current_copy));
         }
     }
 
-    copies.iter().sum()
+    entries
+}
+
+pub fn parse_scratchcard_copies(scratchcard_list: &ScratchCardList) -> usize {
+    cascade(scratchcard_list)
+        .iter()
+        .map(|entry| entry.copies)
+        .sum()
 }
 
 #[cfg(test)]
@@ -105,46 +161,87 @@ mod tests {
     use super::*;
     use utility_2022::{get_input, is_demo_mode};
 
-    // pub mod part1 {
+    pub mod cards {
+        use super::*;
+
+        pub fn demo_cards() -> Vec<ScratchCard> {
+            parse_input(&get_input()).expect("Demo input should parse")
+        }
+
+        #[test]
+        fn test_matches_and_big_numbers() {
+            let cards = parse_input("Card 1: 300 7 | 300  7 1\nCard 2: 1 | 2\n").unwrap();
+
+            assert_eq!(cards[0].matches, 2);
+            assert_eq!(cards[0].points(), 2);
+            assert_eq!(cards[1].matches, 0);
+        }
+
+        #[test]
+        fn test_parse_errors() {
+            assert_eq!(
+                parse_input("Card 1: 1 2 | 3\nCard 2: 1 x | 3\n").unwrap_err(),
+                ParseError::InvalidNumber {
+                    line: 2,
+                    token: "x".to_string()
+                }
+            );
+            assert_eq!(
+                parse_input("Card 1: 1 2 3\n").unwrap_err(),
+                ParseError::Malformed { line: 1 }
+            );
+        }
+
+        #[test]
+        fn test_cascade() {
+            if !is_demo_mode() {
+                return;
+            }
 
-    //     use super::*;
+            let entries = cascade(&ScratchCardList::new(demo_cards()));
+
+            assert_eq!(
+                entries[3],
+                CascadeEntry {
+                    card: 4,
+                    copies: 8,
+                    won_from: vec![(1, 1), (2, 2), (3, 4)],
+                }
+            );
+            assert_eq!(entries[5].won_from, vec![]);
+            assert_eq!(entries.iter().map(|e| e.copies).sum::<usize>(), 30);
+        }
+    }
 
-    //     #[test]
-    //     fn test_demo_input() {
-    //         if !is_demo_mode() {
-    //             return;
-    //         }
+    pub mod part1 {
 
-    //         let input = get_input();
+        use super::*;
 
-    //         let cards = parse_input(&input);
-    //         // print!("{:#?}", cards);
+        #[test]
+        fn test_demo_input() {
+            if !is_demo_mode() {
+                return;
+            }
+
+            let input = get_input();
 
-    //         let mut sum = 0;
-    //         for card in cards {
-    //             let points = card.points();
-    //             println!("Card points: {}", points);
-    //             sum += points;
-    //         }
+            let cards = parse_input(&input).unwrap();
 
-    //         assert_eq!(13, sum);
-    //     }
+            assert_eq!(13, total_points(&cards));
+        }
 
-    //     #[test]
-    //     fn test_input() {
-    //         if is_demo_mode() {
-    //             return;
-    //         }
+        #[test]
+        fn test_input() {
+            if is_demo_mode() {
+                return;
+            }
 
-    //         let input = get_input();
-    //         let sum = parse_input(&input)
-    //             .iter()
-    //             .map(|card| card.points())
-    //             .sum::<u32>();
+            let input = get_input();
+            let sum = total_points(&parse_input(&input).unwrap());
 
-    //         println!("Answer pt1: {}", sum);
-    //     }
-    // }
+            println!("Answer pt1: {}", sum);
+        }
+    }
 
     pub mod part2 {
 
@@ -157,7 +254,7 @@ mod tests {
             }
 
             let input = get_input();
-            let cards = parse_input(&input);
+            let cards = parse_input(&input).unwrap();
             let card_list = ScratchCardList::new(cards);
             let copies = parse_scratchcard_copies(&card_list);
 
@@ -171,7 +268,7 @@ mod tests {
             }
 
             let input = get_input();
-            let cards = parse_input(&input);
+            let cards = parse_input(&input).unwrap();
             let card_list = ScratchCardList::new(cards);
             let copies = parse_scratchcard_copies(&card_list);
 
@@ -1,24 +1,37 @@
 #![allow(dead_code, non_snake_case)]
-use std::ops::Range;
+use std::{collections::HashMap, fmt, ops::Range};
+
+mod piecewise;
+mod validate;
+
+use piecewise::PiecewiseMap;
 
 #[derive(Debug)]
 struct RangedMap {
+    /// Source range, cut at `u64::MAX` if `input + length` overflows
     range: Range<u64>,
     output_start: u64,
+    /// Length as written in the almanac
+    length: u64,
 }
 
 impl RangedMap {
     fn new(input: u64, output: u64, range: u64) -> Self {
         Self {
-            range: input..(input + range),
+            range: input..input.saturating_add(range),
             output_start: output,
+            length: range,
         }
     }
 
     fn map(&self, value: u64) -> u64 {
         let value_idx = value - self.range.start;
-        let new_value = self.output_start + value_idx;
-        new_value
+        self.output_start.saturating_add(value_idx)
+    }
+
+    /// Destination range, cut at `u64::MAX` if it overflows
+    fn output_range(&self) -> Range<u64> {
+        self.output_start..self.output_start.saturating_add(self.length)
     }
 
     fn contains(&self, value: u64) -> bool {
@@ -26,25 +39,27 @@ impl RangedMap {
     }
 }
 
+/// One block of the almanac, e.g. `seed-to-soil map:` and its ranges
 #[derive(Debug)]
 struct XToYMap {
+    source: String,
+    destination: String,
     maps: Vec<RangedMap>,
 }
 
 impl XToYMap {
-    fn new(maps: Vec<RangedMap>) -> Self {
-        Self { maps }
+    fn new(source: String, destination: String, maps: Vec<RangedMap>) -> Self {
+        Self {
+            source,
+            destination,
+            maps,
+        }
     }
 
     fn map(&self, value: u64) -> u64 {
         // Selects the first range that contains the value
 
-        let selected_map = self
-            .maps
-            .iter()
-            .filter(|map| map.contains(value))
-            .take(1)
-            .next();
+        let selected_map = self.maps.iter().find(|map| map.contains(value));
 
         // If a range was found, map the value to the second range
         if let Some(map) = selected_map {
@@ -53,85 +68,22 @@ impl XToYMap {
             value
         }
     }
-}
-
-type SeedList = Vec<u64>;
-
-fn parse_seed_part_2(input: &str) -> SeedList {
-    // E.g. : "seeds: 79 14 55 13"
-    // Where  "seeds: init1 range1 init2 range2 ..."
-    // The range is inclusive (E.g. 1 3 = 1, 2, 3)
-    input
-        .split(" ")
-        .filter_map(|s| s.parse::<u64>().ok())
-        .collect::<Vec<u64>>()
-        .chunks(2)
-        .flat_map(|pair| pair[0]..=(pair[1] + pair[0] - 1))
-        .collect::<SeedList>()
-}
-
-fn parse_input_2(input: &str) -> u64 {
-    let mut lines = input.split("\n\n");
-
-    let seeds = lines.next().unwrap();
-
-    let mut maps = Vec::new();
-
-    // Make the maps
-    for map in lines {
-        let mut lines = map.split("\n");
-
-        // First line is the map header
-        let _ = lines.next();
-
-        // All other lines are the map ranges
-        let mut map_ranges = Vec::new();
-        for range in lines {
-            let mut range = range.split(" ");
-            if range.clone().count() != 3 {
-                continue;
-            }
-            let output = range.next().expect("output").parse::<u64>().unwrap();
-            let input = range.next().expect("input").parse::<u64>().unwrap();
-            let range = range.next().expect("range").parse::<u64>().unwrap();
-
-            map_ranges.push(RangedMap::new(input, output, range));
-        }
-
-        maps.push(map_ranges);
-    }
 
-    let initial_seed_ranges = seeds
-        .split(" ")
-        .filter_map(|s| s.parse::<u64>().ok())
-        .collect::<Vec<u64>>()
-        .chunks(2)
-        .map(|pair| pair[0]..(pair[1] + pair[0]))
-        .collect::<Vec<Range<u64>>>();
+    /// Maps whole ranges of values at once.
+    /// Find the limits of each range and the map, and only
+    /// update the bounds of the ranges
+    fn map_ranges(&self, ranges: &[Range<u64>]) -> Vec<Range<u64>> {
+        let mut new_ranges = vec![];
 
-    println!("Initial seed ranges: {:?}", initial_seed_ranges);
-
-    // Update the seed ranges
-    // Find the limits of the seed range and the map, and only
-    // update the bounds of the ranges
-    let mut new_seed_ranges = initial_seed_ranges.clone();
-
-    for map in maps.iter() {
-        // Transform just one map
-        let seed_ranges = new_seed_ranges.clone();
-        new_seed_ranges.clear();
-
-        for seed_range in seed_ranges {
-            let mut check_intersections = vec![seed_range];
+        for range in ranges {
+            let mut check_intersections = vec![range.clone()];
             let mut new_intersections = vec![];
 
-            while !check_intersections.is_empty() {
-                // Get an intersection
-                let intersection = check_intersections.pop().unwrap();
+            while let Some(intersection) = check_intersections.pop() {
                 let mut mapped = false;
 
                 // This only maps one intersection at the time
-                for submap in map.iter() {
+                for submap in self.maps.iter() {
                     // Check if the intersection is outside  the map
                     // |  |        -> intersection
                     //        | |  -> map
@@ -215,26 +167,401 @@ fn parse_input_2(input: &str) -> u64 {
                     new_intersections.push(intersection);
                 }
             }
+            new_ranges.extend(new_intersections);
+        }
+
+        new_ranges
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum AlmanacError {
+    MissingSeeds,
+    /// A map header that is not `x-to-y map:`
+    BadHeader(String),
+    /// A map line that is not three numbers
+    BadRange(String),
+    /// Two maps start from the same category
+    DuplicateSource(String),
+    /// Following the maps from `seed` comes back to this category
+    Cycle(String),
+    /// Maps that cannot be reached from `seed`, by source category
+    Disconnected(Vec<String>),
+    /// Part 2 reads the seeds as `start length` pairs, but there are this many
+    OddSeedCount(usize),
+    /// A `start length` seed pair that ends past `u64::MAX`
+    SeedRangeOverflow {
+        start: u64,
+        length: u64,
+    },
+}
+
+impl fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlmanacError::MissingSeeds => write!(f, "missing `seeds:` line"),
+            AlmanacError::BadHeader(h) => write!(f, "bad map header `{}`", h),
+            AlmanacError::BadRange(r) => write!(f, "bad map range `{}`", r),
+            AlmanacError::DuplicateSource(c) => write!(f, "more than one map from `{}`", c),
+            AlmanacError::Cycle(c) => write!(f, "maps loop back to `{}`", c),
+            AlmanacError::Disconnected(c) => {
+                write!(f, "maps not reachable from seed: {}", c.join(", "))
+            }
+            AlmanacError::OddSeedCount(n) => {
+                write!(f, "{} seed numbers cannot form `start length` pairs", n)
+            }
+            AlmanacError::SeedRangeOverflow { start, length } => {
+                write!(f, "seed range {} {} overflows u64", start, length)
+            }
+        }
+    }
+}
+
+const FIRST_CATEGORY: &str = "seed";
+
+/// The seeds and the chain of maps from `seed` to the last category
+#[derive(Debug)]
+struct Almanac {
+    seeds: Vec<u64>,
+    /// Maps in chain order, starting at `seed`
+    maps: Vec<XToYMap>,
+}
+
+impl Almanac {
+    /// Parses the almanac. The map blocks can come in any order, but together
+    /// they must form a single chain starting at `seed`
+    fn parse(input: &str) -> Result<Almanac, AlmanacError> {
+        let mut blocks = input.split("\n\n");
+
+        let seeds = blocks
+            .next()
+            .and_then(|line| line.trim().strip_prefix("seeds:"))
+            .ok_or(AlmanacError::MissingSeeds)?
+            .split_whitespace()
+            .map(|s| {
+                s.parse::<u64>()
+                    .map_err(|_| AlmanacError::BadRange(s.to_string()))
+            })
+            .collect::<Result<Vec<u64>, AlmanacError>>()?;
+
+        let mut by_source: HashMap<String, XToYMap> = HashMap::new();
+
+        for block in blocks.filter(|b| !b.trim().is_empty()) {
+            let mut lines = block.lines().filter(|l| !l.trim().is_empty());
+
+            // First line is the map header
+            let header = lines.next().unwrap_or_default().trim();
+            let (source, destination) = header
+                .strip_suffix(" map:")
+                .and_then(|name| name.split_once("-to-"))
+                .ok_or_else(|| AlmanacError::BadHeader(header.to_string()))?;
+
+            // All other lines are the map ranges
+            let map_ranges = lines
+                .map(|line| {
+                    let numbers = line
+                        .split_whitespace()
+                        .map(|n| n.parse::<u64>())
+                        .collect::<Result<Vec<u64>, _>>();
+                    match numbers.as_deref() {
+                        Ok(&[output, input, range]) => Ok(RangedMap::new(input, output, range)),
+                        _ => Err(AlmanacError::BadRange(line.to_string())),
+                    }
+                })
+                .collect::<Result<Vec<RangedMap>, AlmanacError>>()?;
+
+            let map = XToYMap::new(source.to_string(), destination.to_string(), map_ranges);
+            if by_source.insert(source.to_string(), map).is_some() {
+                return Err(AlmanacError::DuplicateSource(source.to_string()));
+            }
+        }
+
+        // Follow the chain from seed
+        let mut maps = vec![];
+        let mut visited = vec![FIRST_CATEGORY.to_string()];
+        let mut category = FIRST_CATEGORY.to_string();
+
+        while let Some(map) = by_source.remove(&category) {
+            category = map.destination.clone();
+            if visited.contains(&category) {
+                return Err(AlmanacError::Cycle(category));
+            }
+            visited.push(category.clone());
+            maps.push(map);
+        }
+
+        if !by_source.is_empty() {
+            let mut unreachable = by_source.into_keys().collect::<Vec<String>>();
+            unreachable.sort();
+            return Err(AlmanacError::Disconnected(unreachable));
+        }
+
+        Ok(Almanac { seeds, maps })
+    }
+
+    /// Categories in chain order, from `seed` to the last one
+    fn categories(&self) -> Vec<&str> {
+        std::iter::once(FIRST_CATEGORY)
+            .chain(self.maps.iter().map(|m| m.destination.as_str()))
+            .collect()
+    }
+
+    /// The maps needed to go from one category to another, if `from` comes
+    /// before `to` in the chain
+    fn maps_between(&self, from: &str, to: &str) -> Option<&[XToYMap]> {
+        let categories = self.categories();
+        let start = categories.iter().position(|&c| c == from)?;
+        let end = categories.iter().position(|&c| c == to)?;
+
+        if start > end {
+            return None;
+        }
+        Some(&self.maps[start..end])
+    }
+
+    /// Maps a value between any two categories, e.g. soil to humidity
+    fn map_value(&self, from: &str, to: &str, value: u64) -> Option<u64> {
+        let maps = self.maps_between(from, to)?;
+        Some(maps.iter().fold(value, |value, map| map.map(value)))
+    }
+
+    /// Maps ranges of values between any two categories
+    fn map_ranges(&self, from: &str, to: &str, ranges: &[Range<u64>]) -> Option<Vec<Range<u64>>> {
+        let maps = self.maps_between(from, to)?;
+        Some(
+            maps.iter()
+                .fold(ranges.to_vec(), |ranges, map| map.map_ranges(&ranges)),
+        )
+    }
+
+    fn last_category(&self) -> &str {
+        self.maps
+            .last()
+            .map_or(FIRST_CATEGORY, |m| m.destination.as_str())
+    }
+
+    /// Part 1: the seeds are individual values
+    fn locations_part_1(&self) -> Vec<u64> {
+        self.seeds
+            .iter()
+            .map(|&seed| {
+                self.map_value(FIRST_CATEGORY, self.last_category(), seed)
+                    .unwrap()
+            })
+            .collect()
+    }
 
-            new_seed_ranges.extend(new_intersections);
+    fn lowest_location_part_1(&self) -> Option<u64> {
+        self.locations_part_1().into_iter().min()
+    }
+
+    /// Part 2: the seeds are `start length` pairs
+    fn seed_ranges(&self) -> Result<Vec<Range<u64>>, AlmanacError> {
+        if !self.seeds.len().is_multiple_of(2) {
+            return Err(AlmanacError::OddSeedCount(self.seeds.len()));
         }
+
+        self.seeds
+            .chunks(2)
+            .map(|pair| {
+                let (start, length) = (pair[0], pair[1]);
+                start
+                    .checked_add(length)
+                    .map(|end| start..end)
+                    .ok_or(AlmanacError::SeedRangeOverflow { start, length })
+            })
+            .collect()
+    }
+
+    /// Composes the maps between two categories into a single function
+    fn compose(&self, from: &str, to: &str) -> Option<PiecewiseMap> {
+        let maps = self.maps_between(from, to)?;
+        Some(maps.iter().fold(PiecewiseMap::identity(), |function, map| {
+            function.then(&PiecewiseMap::from_map(map))
+        }))
+    }
+
+    fn seed_to_location(&self) -> PiecewiseMap {
+        self.compose(FIRST_CATEGORY, self.last_category())
+            .expect("The chain always goes from seed to its last category")
     }
 
-    new_seed_ranges
-        .iter()
-        .map(|range| range.start)
-        .min()
-        .unwrap() as u64
+    fn lowest_location_part_2(&self) -> Result<Option<u64>, AlmanacError> {
+        Ok(self.seed_to_location().min_over(&self.seed_ranges()?))
+    }
 }
 
-const CORRECT_LOCATIONS_PART_1: [u64; 4] = [82, 43, 86, 35];
-const CORRECT_MIN_LOCATION_PART_1: u64 = 13;
+fn parse_input_2(input: &str) -> u64 {
+    Almanac::parse(input)
+        .expect("Invalid almanac")
+        .lowest_location_part_2()
+        .expect("Invalid seed ranges")
+        .expect("No seeds")
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use utility_2022::{get_input, is_demo_mode};
 
+    /// Almanac of the demo input. Reads the input of the current mode, so
+    /// tests check `is_demo_mode()` first
+    pub fn demo_almanac() -> Almanac {
+        Almanac::parse(&get_input()).expect("Demo almanac should parse")
+    }
+
+    const CORRECT_LOCATIONS_PART_1: [u64; 4] = [82, 43, 86, 35];
+    const CORRECT_MIN_LOCATION_PART_1: u64 = 35;
+
+    pub mod almanac {
+        use super::*;
+
+        #[test]
+        fn test_chain() {
+            if !is_demo_mode() {
+                return;
+            }
+
+            let almanac = demo_almanac();
+
+            assert_eq!(
+                almanac.categories(),
+                vec![
+                    "seed",
+                    "soil",
+                    "fertilizer",
+                    "water",
+                    "light",
+                    "temperature",
+                    "humidity",
+                    "location"
+                ]
+            );
+            // Seed 79 has soil 81 and humidity 78
+            assert_eq!(almanac.map_value("soil", "humidity", 81), Some(78));
+            assert_eq!(almanac.map_value("seed", "seed", 79), Some(79));
+            assert_eq!(almanac.map_value("humidity", "soil", 78), None);
+            assert_eq!(almanac.map_value("seed", "weather", 78), None);
+        }
+
+        #[test]
+        fn test_blocks_in_any_order() {
+            let input =
+                "seeds: 1 5\n\nsoil-to-location map:\n10 0 10\n\nseed-to-soil map:\n0 1 1\n";
+            let almanac = Almanac::parse(input).unwrap();
+
+            assert_eq!(almanac.categories(), vec!["seed", "soil", "location"]);
+            assert_eq!(almanac.locations_part_1(), vec![10, 15]);
+        }
+
+        #[test]
+        fn test_ranges_match_composition() {
+            if !is_demo_mode() {
+                return;
+            }
+
+            let almanac = demo_almanac();
+
+            let lowest = almanac
+                .map_ranges("seed", "location", &almanac.seed_ranges().unwrap())
+                .unwrap()
+                .iter()
+                .filter(|range| !range.is_empty())
+                .map(|range| range.start)
+                .min();
+            assert_eq!(Ok(lowest), almanac.lowest_location_part_2());
+        }
+
+        #[test]
+        fn test_header_validation() {
+            let bad_header = "seeds: 1\n\nseed to soil:\n1 2 3\n";
+            assert_eq!(
+                Almanac::parse(bad_header).unwrap_err(),
+                AlmanacError::BadHeader("seed to soil:".to_string())
+            );
+
+            let disconnected = "seeds: 1\n\nseed-to-soil map:\n\nwater-to-light map:\n";
+            assert_eq!(
+                Almanac::parse(disconnected).unwrap_err(),
+                AlmanacError::Disconnected(vec!["water".to_string()])
+            );
+
+            let cycle = "seeds: 1\n\nseed-to-soil map:\n\nsoil-to-seed map:\n";
+            assert_eq!(
+                Almanac::parse(cycle).unwrap_err(),
+                AlmanacError::Cycle("seed".to_string())
+            );
+
+            let duplicate = "seeds: 1\n\nseed-to-soil map:\n\nseed-to-water map:\n";
+            assert_eq!(
+                Almanac::parse(duplicate).unwrap_err(),
+                AlmanacError::DuplicateSource("seed".to_string())
+            );
+        }
+
+        #[test]
+        fn test_seed_range_errors() {
+            let odd = Almanac::parse(
+                "seeds: 1 2 3
+
+seed-to-soil map:
+",
+            )
+            .unwrap();
+            assert_eq!(odd.locations_part_1(), vec![1, 2, 3]);
+            assert_eq!(
+                odd.lowest_location_part_2(),
+                Err(AlmanacError::OddSeedCount(3))
+            );
+
+            let overflow = Almanac::parse(
+                "seeds: 18446744073709551615 1
+
+seed-to-soil map:
+",
+            );
+            assert_eq!(
+                overflow.unwrap().seed_ranges(),
+                Err(AlmanacError::SeedRangeOverflow {
+                    start: u64::MAX,
+                    length: 1,
+                })
+            );
+        }
+    }
+
+    pub mod part1 {
+
+        use super::*;
+
+        #[test]
+        fn test_demo_input() {
+            if !is_demo_mode() {
+                return;
+            }
+
+            let almanac = demo_almanac();
+
+            assert_eq!(almanac.locations_part_1(), CORRECT_LOCATIONS_PART_1);
+            assert_eq!(
+                almanac.lowest_location_part_1(),
+                Some(CORRECT_MIN_LOCATION_PART_1)
+            );
+        }
+
+        #[test]
+        fn test_input() {
+            if is_demo_mode() {
+                return;
+            }
+
+            let input = get_input();
+            let almanac = Almanac::parse(&input).expect("Invalid almanac");
+
+            println!("Answer pt1: {}", almanac.lowest_location_part_1().unwrap());
+        }
+    }
+
     pub mod part2 {
 
         use super::*;
@@ -247,8 +574,6 @@ mod tests {
 
             let input = get_input();
             let min_location = parse_input_2(&input);
-            println!("Answer pt2: {}", min_location);
-
             assert_eq!(min_location, 46);
         }
 
@@ -259,7 +584,6 @@ mod tests {
             }
 
             let input = get_input();
-            println!("Entering parse_input");
             let min_location = parse_input_2(&input);
 
             println!("Answer pt2: {}", min_location);
@@ -0,0 +1,312 @@
+use std::ops::Range;
+
+use crate::XToYMap;
+
+/// Values in `range` are moved by `offset`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Piece {
+    pub range: Range<u64>,
+    pub offset: i128,
+}
+
+/// `value + offset`, clamped to the u64 range instead of wrapping around
+fn shift(value: u64, offset: i128) -> u64 {
+    let shifted = value as i128 + offset;
+    u64::try_from(shifted).unwrap_or(if shifted < 0 { 0 } else { u64::MAX })
+}
+
+impl Piece {
+    /// Values sent past `u64::MAX` saturate there, like in `RangedMap::map`
+    fn apply(&self, value: u64) -> u64 {
+        shift(value, self.offset)
+    }
+
+    /// Where the piece sends its range
+    fn image(&self) -> Range<u64> {
+        self.apply(self.range.start)..self.apply(self.range.end)
+    }
+}
+
+/// A piecewise-linear function on `0..u64::MAX` made of sorted, disjoint
+/// pieces that each shift their values by a constant
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiecewiseMap {
+    pieces: Vec<Piece>,
+}
+
+fn intersect(a: &Range<u64>, b: &Range<u64>) -> Option<Range<u64>> {
+    let range = a.start.max(b.start)..a.end.min(b.end);
+    (!range.is_empty()).then_some(range)
+}
+
+impl PiecewiseMap {
+    pub fn identity() -> PiecewiseMap {
+        PiecewiseMap {
+            pieces: vec![Piece {
+                range: 0..u64::MAX,
+                offset: 0,
+            }],
+        }
+    }
+
+    /// Builds the function of a single almanac map. Where ranges overlap the
+    /// first one wins, like in `XToYMap::map`
+    pub fn from_map(map: &XToYMap) -> PiecewiseMap {
+        let mut breakpoints = vec![0, u64::MAX];
+        for ranged in &map.maps {
+            breakpoints.push(ranged.range.start);
+            breakpoints.push(ranged.range.end);
+        }
+        breakpoints.sort();
+        breakpoints.dedup();
+
+        let pieces = breakpoints
+            .windows(2)
+            .map(|bounds| {
+                let offset = map
+                    .maps
+                    .iter()
+                    .find(|ranged| ranged.contains(bounds[0]))
+                    .map_or(0, |ranged| {
+                        ranged.output_start as i128 - ranged.range.start as i128
+                    });
+                Piece {
+                    range: bounds[0]..bounds[1],
+                    offset,
+                }
+            })
+            .collect();
+
+        PiecewiseMap::merged(pieces)
+    }
+
+    /// Joins neighbouring pieces with the same offset
+    fn merged(pieces: Vec<Piece>) -> PiecewiseMap {
+        let mut merged: Vec<Piece> = vec![];
+
+        for piece in pieces {
+            match merged.last_mut() {
+                Some(last)
+                    if last.offset == piece.offset && last.range.end == piece.range.start =>
+                {
+                    last.range.end = piece.range.end;
+                }
+                _ => merged.push(piece),
+            }
+        }
+
+        PiecewiseMap { pieces: merged }
+    }
+
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    pub fn apply(&self, value: u64) -> u64 {
+        let index = self.pieces.partition_point(|p| p.range.end <= value);
+        match self.pieces.get(index) {
+            Some(piece) if piece.range.contains(&value) => piece.apply(value),
+            _ => value,
+        }
+    }
+
+    /// `next` applied after `self`
+    pub fn then(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        let mut pieces = vec![];
+
+        for piece in &self.pieces {
+            let image = piece.image();
+            let first = next.pieces.partition_point(|p| p.range.end <= image.start);
+
+            for next_piece in &next.pieces[first..] {
+                let Some(overlap) = intersect(&image, &next_piece.range) else {
+                    break;
+                };
+
+                pieces.push(Piece {
+                    range: shift(overlap.start, -piece.offset)..shift(overlap.end, -piece.offset),
+                    offset: piece.offset + next_piece.offset,
+                });
+            }
+
+            // Values saturated at `u64::MAX` are past every range of `next`,
+            // so they keep the offset that saturates them
+            let saturated = shift(image.end, -piece.offset);
+            if saturated < piece.range.end {
+                pieces.push(Piece {
+                    range: saturated..piece.range.end,
+                    offset: piece.offset,
+                });
+            }
+        }
+
+        PiecewiseMap::merged(pieces)
+    }
+
+    /// Input values that land in `range`, as sorted ranges
+    pub fn preimage(&self, range: &Range<u64>) -> Vec<Range<u64>> {
+        let mut ranges = self
+            .pieces
+            .iter()
+            .filter_map(|piece| {
+                let overlap = intersect(&piece.image(), range)?;
+                Some(shift(overlap.start, -piece.offset)..shift(overlap.end, -piece.offset))
+            })
+            .collect::<Vec<Range<u64>>>();
+
+        ranges.sort_by_key(|r| r.start);
+        ranges
+    }
+
+    /// The inverse function, if no two values are sent to the same place
+    pub fn inverse(&self) -> Option<PiecewiseMap> {
+        // Saturated values all land on `u64::MAX`
+        if self
+            .pieces
+            .iter()
+            .any(|p| p.image().end - p.image().start < p.range.end - p.range.start)
+        {
+            return None;
+        }
+
+        let mut pieces = self
+            .pieces
+            .iter()
+            .map(|piece| Piece {
+                range: piece.image(),
+                offset: -piece.offset,
+            })
+            .collect::<Vec<Piece>>();
+        pieces.sort_by_key(|p| p.range.start);
+
+        if pieces
+            .windows(2)
+            .any(|pair| pair[0].range.end > pair[1].range.start)
+        {
+            return None;
+        }
+
+        Some(PiecewiseMap::merged(pieces))
+    }
+
+    /// Lowest output for any value in the given ranges. Every piece is
+    /// increasing, so only the start of each overlap needs checking
+    pub fn min_over(&self, ranges: &[Range<u64>]) -> Option<u64> {
+        let mut ranges = ranges
+            .iter()
+            .filter(|r| !r.is_empty())
+            .cloned()
+            .collect::<Vec<Range<u64>>>();
+        ranges.sort_by_key(|r| r.start);
+
+        let mut lowest = None;
+        let mut first_piece = 0;
+
+        for range in &ranges {
+            first_piece +=
+                self.pieces[first_piece..].partition_point(|p| p.range.end <= range.start);
+
+            for piece in &self.pieces[first_piece..] {
+                let Some(overlap) = intersect(&piece.range, range) else {
+                    break;
+                };
+                let value = piece.apply(overlap.start);
+                lowest = Some(lowest.map_or(value, |l: u64| l.min(value)));
+            }
+        }
+
+        lowest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utility_2022::is_demo_mode;
+
+    use crate::{tests::demo_almanac, Almanac};
+
+    #[test]
+    fn test_composition_matches_chain() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        let almanac = demo_almanac();
+        let function = almanac.seed_to_location();
+
+        for seed in 0..200 {
+            assert_eq!(
+                function.apply(seed),
+                almanac.map_value("seed", "location", seed).unwrap(),
+                "Seed {}",
+                seed
+            );
+        }
+        assert!(function
+            .pieces()
+            .windows(2)
+            .all(|pair| pair[0].range.end == pair[1].range.start));
+    }
+
+    #[test]
+    fn test_inverse() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        let function = demo_almanac().seed_to_location();
+        let inverse = function.inverse().expect("Demo maps are injective");
+
+        for seed in [79, 14, 55, 13, 0, 99, 1_000_000] {
+            assert_eq!(inverse.apply(function.apply(seed)), seed);
+        }
+    }
+
+    #[test]
+    fn test_saturates_past_u64_max() {
+        let almanac = Almanac::parse(
+            "seeds: 40 10
+
+seed-to-soil map:
+18446744073709551610 40 10
+
+soil-to-fertilizer map:
+0 0 10
+",
+        )
+        .unwrap();
+        let function = almanac.compose("seed", "fertilizer").unwrap();
+
+        for seed in 30..60 {
+            assert_eq!(
+                function.apply(seed),
+                almanac.map_value("seed", "fertilizer", seed).unwrap(),
+                "Seed {}",
+                seed
+            );
+        }
+        assert_eq!(function.apply(49), u64::MAX);
+        assert_eq!(function.inverse(), None);
+    }
+
+    #[test]
+    fn test_min_and_preimage() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        let almanac = demo_almanac();
+        let function = almanac.seed_to_location();
+
+        assert_eq!(function.min_over(&almanac.seed_ranges().unwrap()), Some(46));
+        assert_eq!(
+            function.min_over(&[79..80, 14..15, 55..56, 13..14]),
+            Some(35)
+        );
+
+        // Only seed 82 lands in location 46
+        let seeds = function.preimage(&(46..47));
+        assert!(seeds.iter().any(|r| r.contains(&82)));
+        assert_eq!(seeds.iter().map(|r| r.end - r.start).sum::<u64>(), 1);
+    }
+}
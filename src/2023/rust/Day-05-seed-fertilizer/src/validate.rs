@@ -0,0 +1,278 @@
+use std::{fmt, ops::Range};
+
+use crate::{Almanac, XToYMap};
+
+/// A problem found in one almanac map. Entries are 0-based positions of the
+/// ranges inside their map block
+#[derive(Debug, PartialEq)]
+pub enum Diagnostic {
+    /// Two entries map the same source values, only the first one is used
+    OverlappingSources {
+        map: String,
+        first: usize,
+        second: usize,
+        overlap: Range<u64>,
+    },
+    /// Two entries send values to the same destination, so the map is not injective
+    OverlappingDestinations {
+        map: String,
+        first: usize,
+        second: usize,
+        overlap: Range<u64>,
+    },
+    /// `input + range` does not fit in a u64
+    SourceOverflow { map: String, entry: usize },
+    /// `output + range` does not fit in a u64
+    DestinationOverflow { map: String, entry: usize },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::OverlappingSources {
+                map,
+                first,
+                second,
+                overlap,
+            } => write!(
+                f,
+                "{}: entries {} and {} overlap on sources {:?}",
+                map, first, second, overlap
+            ),
+            Diagnostic::OverlappingDestinations {
+                map,
+                first,
+                second,
+                overlap,
+            } => write!(
+                f,
+                "{}: entries {} and {} overlap on destinations {:?}",
+                map, first, second, overlap
+            ),
+            Diagnostic::SourceOverflow { map, entry } => {
+                write!(f, "{}: entry {} source overflows u64", map, entry)
+            }
+            Diagnostic::DestinationOverflow { map, entry } => {
+                write!(f, "{}: entry {} destination overflows u64", map, entry)
+            }
+        }
+    }
+}
+
+/// How much of the source values a map covers
+#[derive(Debug, PartialEq)]
+pub struct Coverage {
+    pub map: String,
+    pub entries: usize,
+    /// Source values covered by at least one entry
+    pub covered: u64,
+    /// Smallest and largest (exclusive) source values covered, if any
+    pub span: Option<Range<u64>>,
+    /// Values inside the span that no entry covers, and map to themselves
+    pub gaps: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub diagnostics: Vec<Diagnostic>,
+    pub coverage: Vec<Coverage>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+/// Finds every pair of overlapping entries in a list of ranges. Sweeps them by
+/// start, keeping the ranges still open at that point, so it runs in
+/// O(n log n) plus the number of pairs found
+fn overlaps(ranges: &[Range<u64>]) -> Vec<(usize, usize, Range<u64>)> {
+    let mut order = (0..ranges.len())
+        .filter(|&i| !ranges[i].is_empty())
+        .collect::<Vec<usize>>();
+    order.sort_by_key(|&i| (ranges[i].start, i));
+
+    let mut found = vec![];
+    let mut active: Vec<usize> = vec![];
+
+    for i in order {
+        active.retain(|&a| ranges[a].end > ranges[i].start);
+        for &a in &active {
+            let overlap = ranges[i].start..ranges[i].end.min(ranges[a].end);
+            found.push((a.min(i), a.max(i), overlap));
+        }
+        active.push(i);
+    }
+
+    found
+}
+
+fn coverage(name: &str, map: &XToYMap) -> Coverage {
+    let mut sources = map
+        .maps
+        .iter()
+        .map(|m| m.range.clone())
+        .filter(|r| !r.is_empty())
+        .collect::<Vec<Range<u64>>>();
+    sources.sort_by_key(|r| r.start);
+
+    let mut covered = 0;
+    let mut end = 0;
+    for range in &sources {
+        let start = range.start.max(end);
+        if range.end > start {
+            covered += range.end - start;
+        }
+        end = end.max(range.end);
+    }
+
+    let span = sources.first().map(|first| first.start..end);
+    let gaps = span
+        .as_ref()
+        .map_or(0, |span| span.end - span.start - covered);
+
+    Coverage {
+        map: name.to_string(),
+        entries: map.maps.len(),
+        covered,
+        span,
+        gaps,
+    }
+}
+
+impl Almanac {
+    /// Checks every map for overlapping ranges and overflows, and reports how
+    /// much of the source values each one covers
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        for map in &self.maps {
+            let name = format!("{}-to-{}", map.source, map.destination);
+
+            for (entry, ranged) in map.maps.iter().enumerate() {
+                if ranged.range.start.checked_add(ranged.length).is_none() {
+                    report.diagnostics.push(Diagnostic::SourceOverflow {
+                        map: name.clone(),
+                        entry,
+                    });
+                }
+                if ranged.output_start.checked_add(ranged.length).is_none() {
+                    report.diagnostics.push(Diagnostic::DestinationOverflow {
+                        map: name.clone(),
+                        entry,
+                    });
+                }
+            }
+
+            let sources = map.maps.iter().map(|m| m.range.clone()).collect::<Vec<_>>();
+            for (first, second, overlap) in overlaps(&sources) {
+                report.diagnostics.push(Diagnostic::OverlappingSources {
+                    map: name.clone(),
+                    first,
+                    second,
+                    overlap,
+                });
+            }
+
+            let destinations = map
+                .maps
+                .iter()
+                .map(|m| m.output_range())
+                .collect::<Vec<_>>();
+            for (first, second, overlap) in overlaps(&destinations) {
+                report
+                    .diagnostics
+                    .push(Diagnostic::OverlappingDestinations {
+                        map: name.clone(),
+                        first,
+                        second,
+                        overlap,
+                    });
+            }
+
+            report.coverage.push(coverage(&name, map));
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utility_2022::is_demo_mode;
+
+    use crate::tests::demo_almanac;
+
+    use super::*;
+
+    #[test]
+    fn test_demo_is_valid() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        let report = demo_almanac().validate();
+
+        assert!(report.is_valid(), "{:?}", report.diagnostics);
+        assert_eq!(
+            report.coverage[0],
+            Coverage {
+                map: "seed-to-soil".to_string(),
+                entries: 2,
+                covered: 50,
+                span: Some(50..100),
+                gaps: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_malformed_almanac() {
+        let input = "seeds: 1 2
+
+seed-to-soil map:
+100 0 10
+200 5 10
+105 30 5
+18446744073709551610 40 10
+";
+        let report = Almanac::parse(input).unwrap().validate();
+
+        assert_eq!(
+            report.diagnostics,
+            vec![
+                Diagnostic::DestinationOverflow {
+                    map: "seed-to-soil".to_string(),
+                    entry: 3,
+                },
+                Diagnostic::OverlappingSources {
+                    map: "seed-to-soil".to_string(),
+                    first: 0,
+                    second: 1,
+                    overlap: 5..10,
+                },
+                Diagnostic::OverlappingDestinations {
+                    map: "seed-to-soil".to_string(),
+                    first: 0,
+                    second: 2,
+                    overlap: 105..110,
+                },
+            ]
+        );
+        assert_eq!(report.coverage[0].covered, 30);
+        assert_eq!(report.coverage[0].gaps, 20);
+        assert_eq!(
+            report.diagnostics[1].to_string(),
+            "seed-to-soil: entries 0 and 1 overlap on sources 5..10"
+        );
+    }
+
+    #[test]
+    fn test_overlaps_inside_a_wider_range() {
+        assert_eq!(
+            overlaps(&[0..10, 2..4, 3..5, 20..30]),
+            vec![(0, 1, 2..4), (0, 2, 3..5), (1, 2, 3..4)]
+        );
+    }
+}
@@ -1,7 +1,11 @@
 #![allow(dead_code, non_snake_case)]
 
+mod physics;
+
 use std::ops::RangeInclusive;
 
+use physics::Physics;
+
 #[derive(Debug)]
 struct Race {
     record_distance: u64,
@@ -32,7 +36,7 @@ fn parse_input_2(input: &str) -> Vec<Race> {
         .next()
         .unwrap()
         .chars()
-        .filter(|char| char.is_digit(10))
+        .filter(|char| char.is_ascii_digit())
         .collect::<String>()
         .parse::<u64>()
         .unwrap();
@@ -41,7 +45,7 @@ fn parse_input_2(input: &str) -> Vec<Race> {
         .next()
         .unwrap()
         .chars()
-        .filter(|char| char.is_digit(10))
+        .filter(|char| char.is_ascii_digit())
         .collect::<String>()
         .parse::<u64>()
         .unwrap();
@@ -80,62 +84,84 @@ fn parse_input_1(input: &str) -> Vec<Race> {
         .collect()
 }
 
-// OJO: Tiene que ser mayor o igual al record
-fn compute_record_range(record_distance: u64, time_limit: u64) -> RangeInclusive<u64> {
-    let R: f64 = record_distance as f64;
-    let T: f64 = time_limit as f64;
-
-    let a = T / 2.0;
-    let b = (-4.0 * R + T.powi(2)).sqrt() / 2.0;
-
-    let h1 = (a - b).ceil();
-    let h2 = (a + b).floor();
-
-    // Check for equal time
-    let compute_distance = |ht: f64| T * ht - ht.powi(2);
-
-    // println!("h1: {}, h2: {}", h1, h2);
-    // println!("d1: {}, d2: {}", compute_distance(h1), compute_distance(h2));
+/// Distance covered when holding the button for `hold` ms out of `time_limit`
+fn distance(hold: u64, time_limit: u64) -> u128 {
+    hold as u128 * (time_limit - hold) as u128
+}
 
-    let h1 = if compute_distance(h1) > R {
-        h1
-    } else {
-        h1 + 1.0
-    } as u64;
+/// Hold times that beat the record, strictly. The distance `h·(T - h)` is
+/// above `R` between the roots `(T ± √(T² - 4R)) / 2`, so this uses an integer
+/// square root on u128 and is exact for any u64 input. The range is empty if
+/// the record cannot be beaten
+fn compute_record_range(record_distance: u64, time_limit: u64) -> RangeInclusive<u64> {
+    let T = time_limit as u128;
+    let R = record_distance as u128;
+
+    let Some(discriminant) = (T * T).checked_sub(4 * R) else {
+        return RangeInclusive::new(1, 0);
+    };
+
+    // Floor of the lower root, which is at most one short of the first
+    // winning hold time
+    let mut first = ((T - discriminant.isqrt()) / 2) as u64;
+    while first <= time_limit / 2 && distance(first, time_limit) <= R {
+        first += 1;
+    }
 
-    let h2 = if compute_distance(h2) > R {
-        h2
-    } else {
-        h2 - 1.0
-    } as u64;
+    if first > time_limit / 2 {
+        return RangeInclusive::new(1, 0);
+    }
 
-    h1..=h2
+    // The distance is symmetric around T / 2
+    first..=time_limit - first
 }
 
 fn count_ways(range: RangeInclusive<u64>) -> u64 {
+    if range.is_empty() {
+        return 0;
+    }
     *range.end() - *range.start() + 1
 }
 
+#[derive(Debug, PartialEq)]
+struct RaceReport {
+    winning_holds: RangeInclusive<u64>,
+    ways: u64,
+    /// Hold time giving the longest distance. `time_limit - optimal_hold`
+    /// goes just as far
+    optimal_hold: u64,
+    best_distance: u128,
+    /// How far the best distance goes past the record, negative if the record
+    /// cannot be beaten
+    margin: i128,
+}
+
+impl Race {
+    fn report(&self) -> RaceReport {
+        Physics::standard().report(self)
+    }
+
+    fn cross_check(&self) -> Result<(), String> {
+        Physics::standard().cross_check(self)
+    }
+}
+
 fn part_1(input: &str) -> u64 {
-    let races = parse_input_1(&input);
-    let possible_scores = races
+    let races = parse_input_1(input);
+    races
         .iter()
         .map(|race| compute_record_range(race.record_distance, race.time_limit_ms))
-        .map(|r| count_ways(r))
-        .collect::<Vec<u64>>();
-
-    possible_scores.iter().fold(1, |acc, x| acc * x)
+        .map(count_ways)
+        .product()
 }
 
 fn part_2(input: &str) -> u64 {
-    let races = parse_input_2(&input);
-    let possible_scores = races
+    let races = parse_input_2(input);
+    races
         .iter()
         .map(|race| compute_record_range(race.record_distance, race.time_limit_ms))
-        .map(|r| count_ways(r))
-        .collect::<Vec<u64>>();
-
-    possible_scores.iter().fold(1, |acc, x| acc * x)
+        .map(count_ways)
+        .product()
 }
 
 #[cfg(test)]
@@ -183,6 +209,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_exact_matches_brute_force() {
+        for time_limit in 0..60 {
+            for record_distance in 0..=time_limit * time_limit / 4 + 2 {
+                let race = Race::new(record_distance, time_limit);
+                assert_eq!(race.cross_check(), Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_race() {
+        // T² is far beyond 2^53, and the record is exactly reached by holding
+        // for 2·10^9 ms, so that hold time must not count
+        let time_limit = 6_000_000_001;
+        let hold = 2_000_000_000;
+        let race = Race::new(hold * (time_limit - hold), time_limit);
+        let report = race.report();
+
+        assert_eq!(report.winning_holds, hold + 1..=time_limit - hold - 1);
+        assert_eq!(report.ways, 2_000_000_000);
+        assert_eq!(report.optimal_hold, 3_000_000_000);
+    }
+
+    #[test]
+    fn test_report() {
+        let report = Race::new(9, 7).report();
+
+        assert_eq!(report.winning_holds, 2..=5);
+        assert_eq!(report.optimal_hold, 3);
+        assert_eq!(report.best_distance, 12);
+        assert_eq!(report.margin, 3);
+
+        let unbeatable = Race::new(13, 7).report();
+        assert_eq!(unbeatable.ways, 0);
+        assert_eq!(unbeatable.margin, -1);
+    }
+
     pub mod part2 {
 
         use super::*;
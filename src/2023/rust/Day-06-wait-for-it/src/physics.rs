@@ -0,0 +1,216 @@
+use std::ops::RangeInclusive;
+
+use crate::{compute_record_range, count_ways, Race, RaceReport};
+
+/// How a boat moves once the button is released. Every millisecond held adds
+/// `acceleration` mm/ms of speed, up to `max_speed`, and the boat then stays
+/// still for `penalty_ms` before it starts moving
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Physics {
+    pub acceleration: u64,
+    pub max_speed: Option<u64>,
+    pub penalty_ms: u64,
+}
+
+impl Physics {
+    /// The puzzle's boats: speed equals hold time, so the distance is `h·(T - h)`
+    pub fn standard() -> Physics {
+        Physics {
+            acceleration: 1,
+            max_speed: None,
+            penalty_ms: 0,
+        }
+    }
+
+    pub fn speed(&self, hold: u64) -> u128 {
+        let speed = hold as u128 * self.acceleration as u128;
+        match self.max_speed {
+            Some(max) => speed.min(max as u128),
+            None => speed,
+        }
+    }
+
+    /// Distance covered when holding for `hold` ms out of `time_limit`.
+    /// Saturates instead of overflowing, which keeps the function unimodal
+    pub fn distance(&self, hold: u64, time_limit: u64) -> u128 {
+        let moving = time_limit
+            .saturating_sub(hold)
+            .saturating_sub(self.penalty_ms);
+        self.speed(hold).saturating_mul(moving as u128)
+    }
+
+    /// Winning hold times from the quadratic formula. Only possible without a
+    /// speed cap, where the distance is `a·h·(T' - h)` with `T' = T - penalty`
+    fn closed_form(&self, race: &Race) -> Option<RangeInclusive<u64>> {
+        if self.max_speed.is_some() {
+            return None;
+        }
+        if self.acceleration == 0 {
+            return Some(RangeInclusive::new(1, 0));
+        }
+
+        // `a·x > R` is the same as `x > ⌊R / a⌋` for integers
+        let time_limit = race.time_limit_ms.saturating_sub(self.penalty_ms);
+        Some(compute_record_range(
+            race.record_distance / self.acceleration,
+            time_limit,
+        ))
+    }
+
+    /// Hold times that beat the record, using the closed form when there is one
+    pub fn winning_holds(&self, race: &Race) -> RangeInclusive<u64> {
+        self.closed_form(race).unwrap_or_else(|| {
+            search_unimodal(
+                |hold| self.distance(hold, race.time_limit_ms),
+                race.time_limit_ms,
+                race.record_distance as u128,
+            )
+        })
+    }
+
+    /// Same as `winning_holds`, trying every hold time. Only meant to
+    /// cross-check the faster solvers on small races
+    pub fn brute_force(&self, race: &Race) -> RangeInclusive<u64> {
+        let record = race.record_distance as u128;
+        let mut winning = (0..=race.time_limit_ms)
+            .filter(|&hold| self.distance(hold, race.time_limit_ms) > record);
+
+        match winning.next() {
+            Some(first) => first..=winning.next_back().unwrap_or(first),
+            None => RangeInclusive::new(1, 0),
+        }
+    }
+
+    pub fn report(&self, race: &Race) -> RaceReport {
+        let winning_holds = self.winning_holds(race);
+        let optimal_hold = peak(
+            |hold| self.distance(hold, race.time_limit_ms),
+            race.time_limit_ms,
+        );
+        let best_distance = self.distance(optimal_hold, race.time_limit_ms);
+
+        RaceReport {
+            ways: count_ways(winning_holds.clone()),
+            winning_holds,
+            optimal_hold,
+            best_distance,
+            margin: best_distance as i128 - race.record_distance as i128,
+        }
+    }
+
+    /// Compares `winning_holds` with trying every hold time. Both return
+    /// `1..=0` when the record cannot be beaten
+    pub fn cross_check(&self, race: &Race) -> Result<(), String> {
+        let fast = self.winning_holds(race);
+        let brute_force = self.brute_force(race);
+
+        if fast == brute_force {
+            Ok(())
+        } else {
+            Err(format!(
+                "Race {:?} with {:?}: fast {:?}, brute force {:?}",
+                race, self, fast, brute_force
+            ))
+        }
+    }
+}
+
+/// First point in `0..=max` where a unimodal function stops increasing. The
+/// function may only be flat at its top
+fn peak(f: impl Fn(u64) -> u128, max: u64) -> u64 {
+    let (mut low, mut high) = (0, max);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if f(mid + 1) > f(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+/// First point in `low..=high` where a monotone predicate turns true
+fn first_true(mut low: u64, mut high: u64, pred: impl Fn(u64) -> bool) -> Option<u64> {
+    if !pred(high) {
+        return None;
+    }
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(mid) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Some(low)
+}
+
+/// Points in `0..=max` where a unimodal function goes above `record`. Finds
+/// the peak, then binary searches each side, so it needs O(log max) calls
+fn search_unimodal(f: impl Fn(u64) -> u128, max: u64, record: u128) -> RangeInclusive<u64> {
+    let top = peak(&f, max);
+    if f(top) <= record {
+        return RangeInclusive::new(1, 0);
+    }
+
+    let first = first_true(0, top, |x| f(x) > record).unwrap_or(top);
+    let last = first_true(top, max, |x| f(x) <= record).map_or(max, |x| x - 1);
+    first..=last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_matches_quadratic() {
+        let physics = Physics::standard();
+
+        for (time_limit, record_distance, ways) in [(7, 9, 4), (15, 40, 8), (30, 200, 9)] {
+            let race = Race::new(record_distance, time_limit);
+            assert_eq!(physics.report(&race), race.report());
+            assert_eq!(physics.report(&race).ways, ways);
+        }
+    }
+
+    #[test]
+    fn test_search_matches_brute_force() {
+        for acceleration in 0..4 {
+            for max_speed in [None, Some(0), Some(3), Some(10)] {
+                for penalty_ms in [0, 2, 7] {
+                    let physics = Physics {
+                        acceleration,
+                        max_speed,
+                        penalty_ms,
+                    };
+
+                    for time_limit in 0..25 {
+                        for record_distance in (0..200).step_by(7) {
+                            let race = Race::new(record_distance, time_limit);
+                            assert_eq!(physics.cross_check(&race), Ok(()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_capped_race() {
+        // Speed 2 per ms held, capped at 6, after a 1 ms penalty. Holding 3 ms
+        // reaches the cap and leaves 6 ms to move, which is the best
+        let physics = Physics {
+            acceleration: 2,
+            max_speed: Some(6),
+            penalty_ms: 1,
+        };
+        let report = physics.report(&Race::new(30, 10));
+
+        assert_eq!(report.optimal_hold, 3);
+        assert_eq!(report.best_distance, 36);
+        assert_eq!(report.margin, 6);
+        // 2: 4·7 = 28, 3: 6·6 = 36, 4: 6·5 = 30
+        assert_eq!(report.winning_holds, 3..=3);
+    }
+}
@@ -1,30 +1,35 @@
 #![allow(dead_code, non_snake_case)]
 
-use std::cmp::Ordering;
-#[derive(Debug)]
-enum Outcome {
-    Win,
-    Loss,
-}
+mod report;
+
+use std::cmp::{Ordering, Reverse};
+
+use report::ranking;
 
-pub type CardDeck = [char; 5];
+/// Largest number of card ranks a rule set can have, so counting the cards of
+/// a hand fits in a fixed array
+const MAX_RANKS: usize = 64;
+
+/// Longest hand whose strength fits in a u128: 4 bits for the type and 7 bits
+/// per card
+const MAX_PACKED_CARDS: usize = 17;
 
 #[derive(Debug)]
 struct Hand {
-    cards: CardDeck,
+    /// The hand as written, with a suit after every rank for suited rules
+    text: String,
+    suited: bool,
     hand_type: HandType,
+    /// Position of each card in the rules' card order, smaller is stronger
+    card_values: Vec<u8>,
+    /// Type and card values packed so comparing hands is a single integer
+    /// comparison, smaller is stronger. `None` for hands too long to pack
+    strength: Option<u128>,
 }
 
 impl Display for Hand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let card = self
-            .cards
-            .iter()
-            .map(|c| c.to_string())
-            .collect::<Vec<String>>()
-            .join("");
-
-        write!(f, "{}:{:?}", card, self.hand_type)
+        write!(f, "{}:{:?}", self.text(), self.hand_type)
     }
 }
 
@@ -46,130 +51,301 @@ impl Display for HandWithBid {
     }
 }
 
-pub const CARD_ORDER: [char; 13] = [
-    'A', 'K', 'Q', 'T', '9', '8', '7', '6', '5', '4', '3', '2', 'J',
-];
+/// How hands are read: which cards are stronger, which ones act as wildcards
+/// and which poker categories count when working out the hand type
+#[derive(Debug, Clone, PartialEq)]
+struct Rules {
+    /// Strongest card first
+    card_order: Vec<char>,
+    /// Cards that count as whichever card makes the best hand
+    wildcards: Vec<char>,
+    /// Cards are written as a rank followed by a suit, like `Ah`
+    suited: bool,
+    /// Cards forming a run in `card_order` make a straight
+    straights: bool,
+    /// Cards all of the same suit make a flush. Needs `suited`
+    flushes: bool,
+    /// Position in `card_order` of every ASCII card, `u8::MAX` if missing
+    rank_index: [u8; 128],
+    /// Bit set of the positions of the wildcards in `card_order`
+    wildcard_mask: u64,
+}
+
+impl Rules {
+    /// Rules without suits, straights or flushes. The lookup tables are built
+    /// here so reading a hand never has to search the card order
+    fn new(card_order: &str, wildcards: &str) -> Rules {
+        let card_order = card_order.chars().collect::<Vec<char>>();
+        assert!(card_order.len() <= MAX_RANKS, "Too many card ranks");
+
+        let mut rank_index = [u8::MAX; 128];
+        for (index, &card) in card_order.iter().enumerate() {
+            assert!(card.is_ascii(), "Cards must be ASCII");
+            rank_index[card as usize] = index as u8;
+        }
+
+        let wildcards = wildcards.chars().collect::<Vec<char>>();
+        let wildcard_mask = wildcards.iter().fold(0, |mask, &card| {
+            let index = rank_index[card as usize];
+            assert!(index != u8::MAX, "Wildcards must be in the card order");
+            mask | 1 << index
+        });
+
+        Rules {
+            card_order,
+            wildcards,
+            suited: false,
+            straights: false,
+            flushes: false,
+            rank_index,
+            wildcard_mask,
+        }
+    }
+
+    /// Part 1: `J` is a jack, between the queen and the ten
+    fn standard() -> Rules {
+        Rules::new("AKQJT98765432", "")
+    }
+
+    /// Part 2: `J` is a joker, the weakest card but a wildcard
+    fn jokers() -> Rules {
+        Rules::new("AKQT98765432J", "J")
+    }
+
+    /// Suited cards with straights and flushes, like in poker. Aces are high
+    /// only
+    fn poker() -> Rules {
+        Rules {
+            suited: true,
+            straights: true,
+            flushes: true,
+            ..Rules::new("AKQJT98765432", "")
+        }
+    }
+
+    fn card_value(&self, card: char) -> usize {
+        // The smaller the index, the higher the value
+        match self.rank_index.get(card as usize) {
+            Some(&index) if index != u8::MAX => index as usize,
+            _ => panic!("Card not found: {}", card),
+        }
+    }
+
+    fn is_wildcard(&self, card: char) -> bool {
+        self.is_wildcard_value(self.card_value(card) as u8)
+    }
 
-fn get_card_value(card: char) -> usize {
-    // The smaller the index, the higher the value
-    CARD_ORDER
-        .iter()
-        .position(|&c| c == card)
-        .expect("Card not found")
+    fn is_wildcard_value(&self, value: u8) -> bool {
+        self.wildcard_mask & 1 << value != 0
+    }
 }
 
 impl Hand {
-    fn new(cards: CardDeck) -> Hand {
-        let hand_type = HandType::new(cards);
-        Hand { cards, hand_type }
+    /// Reads a hand of any size, like `KTJJT`, or `KhTh9c` for suited rules
+    fn parse(text: &str, rules: &Rules) -> Hand {
+        let step = if rules.suited { 2 } else { 1 };
+        assert!(
+            !rules.suited || text.chars().count().is_multiple_of(2),
+            "Suited cards need a rank and a suit: {}",
+            text
+        );
+
+        let card_values = text
+            .chars()
+            .step_by(step)
+            .map(|card| rules.card_value(card) as u8)
+            .collect::<Vec<u8>>();
+        let suits = rules.suited.then(|| text.chars().skip(1).step_by(2));
+        let hand_type = HandType::new(&card_values, suits, rules);
+
+        Hand {
+            text: text.to_string(),
+            suited: rules.suited,
+            hand_type,
+            strength: Hand::pack(hand_type, &card_values),
+            card_values,
+        }
     }
 
-    fn beats(&self, other: &Hand) -> Outcome {
-        if self.hand_type.value() == other.hand_type.value() {
-            for (c1, c2) in self.cards.iter().zip(other.cards.iter()) {
-                if get_card_value(*c1) == get_card_value(*c2) {
-                    continue;
-                }
+    /// Card ranks, without their suits
+    fn cards(&self) -> Vec<char> {
+        let step = if self.suited { 2 } else { 1 };
+        self.text.chars().step_by(step).collect()
+    }
 
-                return if get_card_value(*c1) < get_card_value(*c2) {
-                    Outcome::Win
-                } else {
-                    Outcome::Loss
-                };
-            }
+    /// Suit of each card, empty for rules without suits
+    fn suits(&self) -> Vec<char> {
+        if !self.suited {
+            return vec![];
+        }
+        self.text.chars().skip(1).step_by(2).collect()
+    }
 
-            unreachable!("Hands are tied");
+    /// Packs the type and then every card value plus one, padding with zeros,
+    /// so the integers sort like `(type, card_values)` do
+    fn pack(hand_type: HandType, card_values: &[u8]) -> Option<u128> {
+        if card_values.len() > MAX_PACKED_CARDS {
+            return None;
         }
 
-        if self.hand_type.value() < other.hand_type.value() {
-            Outcome::Win
-        } else {
-            Outcome::Loss
+        let strength = card_values
+            .iter()
+            .fold(hand_type.value() as u128, |strength, &value| {
+                strength << 7 | (value as u128 + 1)
+            });
+        Some(strength << (7 * (MAX_PACKED_CARDS - card_values.len())))
+    }
+
+    /// The hand as it is written in the input
+    fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Hands compare by type first, then card by card from the left. Suits never
+/// break ties, and identical hands are equal
+impl Ord for Hand {
+    fn cmp(&self, other: &Hand) -> Ordering {
+        match (self.strength, other.strength) {
+            (Some(a), Some(b)) => a.cmp(&b).reverse(),
+            _ => (self.hand_type.value(), &self.card_values)
+                .cmp(&(other.hand_type.value(), &other.card_values))
+                .reverse(),
         }
     }
 }
 
-#[derive(Debug)]
+impl PartialOrd for Hand {
+    fn partial_cmp(&self, other: &Hand) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Hand {
+    fn eq(&self, other: &Hand) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Hand {}
+
+/// Strongest first. Straights and flushes only appear when the rules allow them
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum HandType {
     FiveOfAKind,
+    StraightFlush,
     FourOfAKind,
     FullHouse,
+    Flush,
+    Straight,
     ThreeOfAKind,
     TwoPair,
     OnePair,
     HighCard,
 }
 
-use std::collections::HashMap;
 use std::fmt::Display;
 
 use HandType::*;
 
 impl HandType {
-    fn new(hand: CardDeck) -> HandType {
-        let mut hand_counts: HashMap<char, u8> = HashMap::new();
-
-        for card in hand.iter() {
-            let count = hand_counts.entry(*card).or_insert(0);
-            *count += 1;
+    /// Works out the type from the card values of a hand, as given by
+    /// `Rules::card_value`. Counts cards in a fixed array and keeps only the
+    /// two largest counts, so it never allocates
+    fn new(values: &[u8], suits: Option<impl Iterator<Item = char>>, rules: &Rules) -> HandType {
+        let mut counts = [0usize; MAX_RANKS];
+        let mut wildcards = 0;
+        let (mut lowest, mut highest) = (u8::MAX, 0);
+
+        for &value in values {
+            if rules.is_wildcard_value(value) {
+                wildcards += 1;
+            } else {
+                counts[value as usize] += 1;
+                lowest = lowest.min(value);
+                highest = highest.max(value);
+            }
         }
 
-        let jokers_count = hand_counts.get(&'J').unwrap_or(&0).clone();
-
-        if jokers_count == 5 {
-            return FiveOfAKind;
+        // The count signature: how many times the two most common cards appear
+        let (mut top, mut second) = (0, 0);
+        for &count in &counts[..rules.card_order.len()] {
+            if count > top {
+                second = top;
+                top = count;
+            } else if count > second {
+                second = count;
+            }
         }
 
-        // Reset the jokers
-        hand_counts.remove(&'J');
-
-        let mut counts: Vec<u8> = hand_counts.values().cloned().collect();
-        counts.sort();
-        counts.reverse();
-
-        // Add jokers to highest count
-        counts[0] += jokers_count;
-
-        match counts.len() {
-            1 => FiveOfAKind,
-            2 => {
-                if counts[0] == 4 {
-                    FourOfAKind
-                } else {
-                    FullHouse
+        // Wildcards always do best joining the most common card
+        let by_counts = HandType::from_signature(top + wildcards, second);
+
+        // Wildcards fill the gaps of a straight and take any suit
+        let len = values.len();
+        let straight = rules.straights
+            && len >= 2
+            && len <= rules.card_order.len() - rules.wildcards.len()
+            && top <= 1
+            && (wildcards == len || ((highest - lowest) as usize) < len);
+        let flush = rules.flushes
+            && len >= 2
+            && suits.is_some_and(|suits| {
+                let mut suits = values
+                    .iter()
+                    .zip(suits)
+                    .filter(|(&value, _)| !rules.is_wildcard_value(value))
+                    .map(|(_, suit)| suit);
+                match suits.next() {
+                    Some(first) => suits.all(|suit| suit == first),
+                    None => true,
                 }
-            }
-            3 => {
-                if counts[0] == 3 {
-                    ThreeOfAKind
-                } else {
-                    TwoPair
-                }
-            }
-            4 => OnePair,
-            5 => HighCard,
-            _ => unreachable!(
-                "Invalid hand {}\nwith counts {:?}",
-                hand.iter().collect::<String>(),
-                counts
-            ),
+            });
+
+        let candidates = [
+            Some(by_counts),
+            (straight && flush).then_some(StraightFlush),
+            flush.then_some(Flush),
+            straight.then_some(Straight),
+        ];
+        candidates
+            .into_iter()
+            .flatten()
+            .min_by_key(|hand_type| hand_type.value())
+            .unwrap_or(by_counts)
+    }
+
+    /// Type of a hand whose two most common cards appear `top` and `second`
+    /// times
+    fn from_signature(top: usize, second: usize) -> HandType {
+        match (top, second) {
+            (5.., _) => FiveOfAKind,
+            (4, _) => FourOfAKind,
+            (3, 2..) => FullHouse,
+            (3, _) => ThreeOfAKind,
+            (2, 2..) => TwoPair,
+            (2, _) => OnePair,
+            _ => HighCard,
         }
     }
 
     fn value(&self) -> usize {
         match self {
             FiveOfAKind => 0,
-            FourOfAKind => 1,
-            FullHouse => 2,
-            ThreeOfAKind => 3,
-            TwoPair => 4,
-            OnePair => 5,
-            HighCard => 6,
+            StraightFlush => 1,
+            FourOfAKind => 2,
+            FullHouse => 3,
+            Flush => 4,
+            Straight => 5,
+            ThreeOfAKind => 6,
+            TwoPair => 7,
+            OnePair => 8,
+            HighCard => 9,
         }
     }
 }
 
-fn parse_input(input: &str) -> Vec<HandWithBid> {
+fn parse_input(input: &str, rules: &Rules) -> Vec<HandWithBid> {
     // Example inputs:
     // 32T3K 765
     // T55J5 684
@@ -181,8 +357,7 @@ fn parse_input(input: &str) -> Vec<HandWithBid> {
         .map(|line| {
             let mut splits = line.split(" ");
 
-            let hand = splits.next().unwrap().chars().collect::<Vec<char>>();
-            let hand = Hand::new(hand.try_into().expect("Expected 5 cards"));
+            let hand = Hand::parse(splits.next().unwrap(), rules);
             let bid = splits.next().unwrap().parse::<usize>().unwrap();
 
             HandWithBid::new(hand, bid)
@@ -190,31 +365,27 @@ fn parse_input(input: &str) -> Vec<HandWithBid> {
         .collect()
 }
 
-fn sort_hands(hands: &mut Vec<HandWithBid>) {
-    hands.sort_by(|a, b| {
-        let outcome = a.hand.beats(&b.hand);
+/// Sorts from the weakest hand to the strongest, so the index is the rank - 1.
+/// When every hand has a packed strength, sorts those integers instead of
+/// moving whole hands around on each comparison
+fn sort_hands(hands: &mut [HandWithBid]) {
+    if hands.iter().all(|h| h.hand.strength.is_some()) {
+        hands.sort_by_cached_key(|h| Reverse(h.hand.strength.unwrap_or_default()));
+    } else {
+        hands.sort_by(|a, b| a.hand.cmp(&b.hand))
+    }
+}
 
-        match outcome {
-            Outcome::Loss => Ordering::Less,
-            Outcome::Win => Ordering::Greater,
-        }
-    })
+fn total_winnings(input: &str, rules: &Rules) -> usize {
+    ranking(input, rules).total
 }
 
-fn part_1(input: &str) -> usize {
-    let mut hands = parse_input(input);
-    sort_hands(&mut hands);
-    for hand in hands.iter().enumerate() {
-        println!("{}:{}", hand.0 + 1, hand.1);
-    }
-    hands
-        .iter()
-        .enumerate()
-        .map(|(idx, hand)| {
-            let total = hand.bid * (idx + 1);
-            total
-        })
-        .sum::<usize>()
+pub fn part_1(input: &str) -> usize {
+    total_winnings(input, &Rules::standard())
+}
+
+pub fn part_2(input: &str) -> usize {
+    total_winnings(input, &Rules::jokers())
 }
 
 #[cfg(test)]
@@ -222,99 +393,136 @@ mod tests {
     use super::*;
     use utility_2022::{get_input, is_demo_mode};
 
-    // pub mod part1 {
-
-    //     use super::*;
-
-    //     #[test]
-    //     fn test_demo_input() {
-    //         if !is_demo_mode() {
-    //             return;
-    //         }
-
-    //         let input = get_input();
-    //         let mut hands = parse_input(&input);
-
-    //         // for hand in hands.iter() {
-    //         //     println!("{:?}", hand);
-    //         // }
-
-    //         assert_eq!(hands.len(), 5);
-
-    //         assert!(
-    //             matches!(hands[0].hand.hand_type, HandType::OnePair),
-    //             "{} has {:?}; OnePair do not match",
-    //             hands[0].hand.cards.iter().collect::<String>(),
-    //             hands[0].hand.hand_type
-    //         );
-    //         assert!(
-    //             matches!(hands[1].hand.hand_type, HandType::ThreeOfAKind),
-    //             "{} has {:?}; TwoPair do not match",
-    //             hands[1].hand.cards.iter().collect::<String>(),
-    //             hands[1].hand.hand_type
-    //         );
-    //         assert!(
-    //             matches!(hands[2].hand.hand_type, HandType::TwoPair),
-    //             "{} has {:?}; TwoPair do not match",
-    //             hands[2].hand.cards.iter().collect::<String>(),
-    //             hands[2].hand.hand_type
-    //         );
-    //         assert!(
-    //             matches!(hands[3].hand.hand_type, HandType::TwoPair),
-    //             "{} has {:?}; ThreeOfAKind do not match",
-    //             hands[3].hand.cards.iter().collect::<String>(),
-    //             hands[3].hand.hand_type
-    //         );
-    //         assert!(
-    //             matches!(hands[4].hand.hand_type, HandType::ThreeOfAKind),
-    //             "{} has {:?}; ThreeOfAKind do not match",
-    //             hands[4].hand.cards.iter().collect::<String>(),
-    //             hands[4].hand.hand_type
-    //         );
-
-    //         let c1 = Hand::new("KK677".chars().collect::<Vec<char>>().try_into().unwrap());
-    //         let c2 = Hand::new("KTJJT".chars().collect::<Vec<char>>().try_into().unwrap());
-
-    //         assert!(
-    //             matches!(c1.beats(&c2), Outcome::Win),
-    //             "{} should beat {}",
-    //             c1.cards.iter().collect::<String>(),
-    //             c2.cards.iter().collect::<String>()
-    //         );
-
-    //         println!("Before sort:");
-    //         for hand in hands.iter() {
-    //             println!("{:?}", hand.hand.cards.iter().collect::<String>());
-    //         }
-    //         sort_hands(&mut hands);
-    //         println!("After sort:");
-    //         for hand in hands.iter() {
-    //             println!("{:?}", hand.hand.cards.iter().collect::<String>());
-    //         }
-
-    //         let total = hands
-    //             .iter()
-    //             .enumerate()
-    //             .map(|(idx, hand)| {
-    //                 let total = hand.bid * (idx + 1);
-    //                 total
-    //             })
-    //             .sum::<usize>();
-
-    //         assert_eq!(total, 6440);
-    //     }
-
-    //     #[test]
-    //     fn test_input() {
-    //         if is_demo_mode() {
-    //             return;
-    //         }
-    //         let input = get_input();
-    //         let sum = part_1(&input);
-
-    //         println!("Answer pt1: {}", sum);
-    //     }
-    // }
+    #[test]
+    fn test_poker_categories() {
+        let poker = Rules::poker();
+
+        assert_eq!(Hand::parse("AhKhQhJhTh", &poker).hand_type, StraightFlush);
+        assert_eq!(Hand::parse("2h3h4h5h7h", &poker).hand_type, Flush);
+        assert_eq!(Hand::parse("9c8dTh7s6s", &poker).hand_type, Straight);
+        assert_eq!(Hand::parse("AhAdAcKsKh", &poker).hand_type, FullHouse);
+        assert!(Hand::parse("2h3h4h5h7h", &poker) > Hand::parse("9c8dTh7s6s", &poker));
+        // Suits never break ties
+        assert_eq!(
+            Hand::parse("AhKd9c8s2h", &poker),
+            Hand::parse("AsKc9h8d2d", &poker)
+        );
+
+        // Wildcards fill the gap of a straight, which beats a pair
+        let jokers = Rules {
+            straights: true,
+            ..Rules::jokers()
+        };
+        assert_eq!(Hand::parse("9TQKJ", &jokers).hand_type, Straight);
+        assert_eq!(Hand::parse("9TQ2J", &jokers).hand_type, OnePair);
+    }
+
+    #[test]
+    fn test_any_hand_size() {
+        let rules = Rules::standard();
+
+        assert_eq!(Hand::parse("KKKKKK7", &rules).hand_type, FiveOfAKind);
+        assert_eq!(Hand::parse("AAKK3QQ", &rules).hand_type, TwoPair);
+        assert_eq!(Hand::parse("AK", &rules).hand_type, HighCard);
+        assert!(Hand::parse("22", &rules) > Hand::parse("AK", &rules));
+
+        // More cards of a kind than a u8 can count
+        let long = Hand::parse(&"2".repeat(300), &rules);
+        assert_eq!(long.hand_type, FiveOfAKind);
+        assert!(Hand::parse(&"3".repeat(300), &rules) > long);
+        let jokers = Hand::parse(&format!("A{}", "J".repeat(299)), &Rules::jokers());
+        assert_eq!(jokers.hand_type, FiveOfAKind);
+    }
+
+    #[test]
+    #[ignore = "timing check, run with `cargo test --release -- --ignored`"]
+    fn test_rank_million_hands() {
+        const RANKS: &[u8] = b"AKQJT98765432";
+        const SUITS: &[u8] = b"hdcs";
+
+        // Knuth's MMIX multiplier and increment, fixed seed for repeatable hands
+        let mut seed: u64 = 7;
+        let mut next = |modulo: usize| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 33) as usize % modulo
+        };
+        let input = (0..1_000_000)
+            .map(|bid| {
+                let cards = (0..5)
+                    .flat_map(|_| [RANKS[next(RANKS.len())], SUITS[next(SUITS.len())]])
+                    .map(char::from)
+                    .collect::<String>();
+                format!("{} {}", cards, bid)
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let start = std::time::Instant::now();
+        let mut hands = parse_input(&input, &Rules::poker());
+        sort_hands(&mut hands);
+        let elapsed = start.elapsed();
+
+        assert!(hands.windows(2).all(|pair| pair[0].hand <= pair[1].hand));
+        assert!(hands.iter().any(|h| h.hand.hand_type == StraightFlush));
+        assert_eq!(hands.len(), 1_000_000);
+        assert!(elapsed.as_millis() < 1000, "Took {:?}", elapsed);
+    }
+
+    #[test]
+    fn test_ties_and_ordering() {
+        let rules = Rules::standard();
+
+        assert_eq!(
+            Hand::parse("KK677", &rules).cmp(&Hand::parse("KK677", &rules)),
+            Ordering::Equal
+        );
+        assert!(Hand::parse("KK677", &rules) > Hand::parse("KTJJT", &rules));
+        assert!(Hand::parse("JJJJJ", &rules) > Hand::parse("AAAAK", &rules));
+
+        let jokers = Rules::jokers();
+        assert!(Hand::parse("KTJJT", &jokers) > Hand::parse("QQQJA", &jokers));
+        assert!(Hand::parse("JKKK2", &jokers) < Hand::parse("QQQQ2", &jokers));
+        assert_eq!(Hand::parse("JJJJJ", &jokers).hand_type, FiveOfAKind);
+    }
+
+    pub mod part1 {
+
+        use super::*;
+
+        #[test]
+        fn test_demo_input() {
+            if !is_demo_mode() {
+                return;
+            }
+
+            let input = get_input();
+            let hands = parse_input(&input, &Rules::standard());
+
+            let types = hands
+                .iter()
+                .map(|h| h.hand.hand_type)
+                .collect::<Vec<HandType>>();
+            assert_eq!(
+                types,
+                vec![OnePair, ThreeOfAKind, TwoPair, TwoPair, ThreeOfAKind]
+            );
+
+            assert_eq!(part_1(&input), 6440);
+        }
+
+        #[test]
+        fn test_input() {
+            if is_demo_mode() {
+                return;
+            }
+            let input = get_input();
+            let sum = part_1(&input);
+
+            println!("Answer pt1: {}", sum);
+        }
+    }
 
     pub mod part2 {
 
@@ -327,7 +535,7 @@ mod tests {
             }
 
             let input = get_input();
-            let result = part_1(&input);
+            let result = part_2(&input);
 
             assert_eq!(result, 5905);
         }
@@ -338,7 +546,7 @@ mod tests {
                 return;
             }
             let input = get_input();
-            let sum = part_1(&input);
+            let sum = part_2(&input);
 
             println!("Answer pt2: {}", sum);
         }
@@ -0,0 +1,13 @@
+use day_07_camel_cards::{part_1, part_2};
+use utility_2022::get_input;
+
+/// Usage: `cargo run`
+///
+/// Prints the total winnings of the puzzle input with the standard rules
+/// (part 1) and with jokers (part 2)
+fn main() {
+    let input = get_input();
+
+    println!("Answer pt1: {}", part_1(&input));
+    println!("Answer pt2: {}", part_2(&input));
+}
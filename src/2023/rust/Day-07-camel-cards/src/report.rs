@@ -0,0 +1,323 @@
+use std::{cmp::Ordering, fmt};
+
+use crate::{parse_input, sort_hands, Hand, HandType, Rules};
+
+/// One line of the ranking, weakest hand first
+#[derive(Debug, PartialEq)]
+pub struct RankedHand {
+    pub rank: usize,
+    pub cards: String,
+    pub bid: usize,
+    pub winnings: usize,
+    pub hand_type: HandType,
+    /// Card the wildcards stood in for to reach `hand_type`, if the hand has any
+    pub substitution: Option<char>,
+}
+
+#[derive(Debug)]
+pub struct RankingReport {
+    pub hands: Vec<RankedHand>,
+    pub total: usize,
+}
+
+impl Rules {
+    /// The card every wildcard in `cards` copies: the most common other card,
+    /// the stronger one on ties. A hand of only wildcards copies the strongest
+    /// card. `None` if there are no wildcards in the hand. Only meaningful
+    /// for types made of repeated cards, not for straights or flushes
+    pub fn substitution(&self, cards: &[char]) -> Option<char> {
+        if !cards.iter().any(|&c| self.is_wildcard(c)) {
+            return None;
+        }
+
+        self.card_order
+            .iter()
+            .copied()
+            .filter(|&card| !self.is_wildcard(card))
+            .max_by_key(|&card| {
+                let count = cards.iter().filter(|&&c| c == card).count();
+                (count, std::cmp::Reverse(self.card_value(card)))
+            })
+    }
+}
+
+/// Ranks every hand of the input and works out its winnings
+pub fn ranking(input: &str, rules: &Rules) -> RankingReport {
+    let mut hands = parse_input(input, rules);
+    sort_hands(&mut hands);
+
+    let hands = hands
+        .iter()
+        .enumerate()
+        .map(|(idx, hand)| RankedHand {
+            rank: idx + 1,
+            cards: hand.hand.text().to_string(),
+            bid: hand.bid,
+            winnings: hand.bid * (idx + 1),
+            hand_type: hand.hand.hand_type,
+            substitution: rules.substitution(&hand.hand.cards()),
+        })
+        .collect::<Vec<RankedHand>>();
+
+    RankingReport {
+        total: hands.iter().map(|h| h.winnings).sum(),
+        hands,
+    }
+}
+
+impl RankingReport {
+    pub fn to_text(&self) -> String {
+        let mut lines = self
+            .hands
+            .iter()
+            .map(|h| {
+                let substitution = h
+                    .substitution
+                    .map(|card| format!(" (wildcards as {})", card))
+                    .unwrap_or_default();
+                format!(
+                    "{:>4}. {} {:?}{}: bid {} x {} = {}",
+                    h.rank, h.cards, h.hand_type, substitution, h.bid, h.rank, h.winnings
+                )
+            })
+            .collect::<Vec<String>>();
+
+        lines.push(format!("Total winnings: {}", self.total));
+        lines.join("\n")
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut lines = vec!["rank,hand,bid,winnings,type,substitution".to_string()];
+
+        for h in &self.hands {
+            lines.push(format!(
+                "{},{},{},{},{:?},{}",
+                h.rank,
+                h.cards,
+                h.bid,
+                h.winnings,
+                h.hand_type,
+                h.substitution.map(String::from).unwrap_or_default()
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// What decided a comparison between two hands
+#[derive(Debug, PartialEq)]
+pub enum Reason {
+    HandType {
+        first: HandType,
+        second: HandType,
+    },
+    /// Both hands have the same type, the card at `position` (0-based) differs
+    Card {
+        position: usize,
+        first: char,
+        second: char,
+    },
+    /// Same type and every card of the shorter hand matches the other one.
+    /// Like `Hand::cmp`, the shorter hand wins
+    Length {
+        first: usize,
+        second: usize,
+    },
+    Identical,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Explanation {
+    pub first: String,
+    pub second: String,
+    /// How the first hand compares to the second
+    pub ordering: Ordering,
+    pub reason: Reason,
+}
+
+/// Compares two hands read with the same rules and says why one wins
+pub fn explain(a: &Hand, b: &Hand) -> Explanation {
+    let reason = if a.hand_type != b.hand_type {
+        Reason::HandType {
+            first: a.hand_type,
+            second: b.hand_type,
+        }
+    } else {
+        let (cards_a, cards_b) = (a.cards(), b.cards());
+        let shared = cards_a.len().min(cards_b.len());
+        match (0..shared).find(|&i| a.card_values[i] != b.card_values[i]) {
+            Some(position) => Reason::Card {
+                position,
+                first: cards_a[position],
+                second: cards_b[position],
+            },
+            None if cards_a.len() != cards_b.len() => Reason::Length {
+                first: cards_a.len(),
+                second: cards_b.len(),
+            },
+            None => Reason::Identical,
+        }
+    };
+
+    Explanation {
+        first: a.text().to_string(),
+        second: b.text().to_string(),
+        ordering: a.cmp(b),
+        reason,
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Always name the winner first
+        let swapped = self.ordering == Ordering::Less;
+        let (winner, loser) = if swapped {
+            (&self.second, &self.first)
+        } else {
+            (&self.first, &self.second)
+        };
+
+        match &self.reason {
+            Reason::Identical => write!(f, "{} ties with {}", self.first, self.second),
+            Reason::HandType { first, second } => {
+                let (w, l) = if swapped {
+                    (second, first)
+                } else {
+                    (first, second)
+                };
+                write!(f, "{} beats {}: {:?} beats {:?}", winner, loser, w, l)
+            }
+            Reason::Card {
+                position,
+                first,
+                second,
+            } => {
+                let (w, l) = if swapped {
+                    (second, first)
+                } else {
+                    (first, second)
+                };
+                write!(
+                    f,
+                    "{} beats {}: same type, card {} is {} against {}",
+                    winner,
+                    loser,
+                    position + 1,
+                    w,
+                    l
+                )
+            }
+            Reason::Length { first, second } => write!(
+                f,
+                "{} beats {}: same type and cards, {} cards against {}",
+                winner,
+                loser,
+                first.min(second),
+                first.max(second)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utility_2022::{get_input, is_demo_mode};
+
+    use super::*;
+
+    #[test]
+    fn test_ranking() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        let report = ranking(&get_input(), &Rules::jokers());
+
+        assert_eq!(report.total, 5905);
+        assert_eq!(
+            report.hands[4],
+            RankedHand {
+                rank: 5,
+                cards: "KTJJT".to_string(),
+                bid: 220,
+                winnings: 1100,
+                hand_type: HandType::FourOfAKind,
+                substitution: Some('T'),
+            }
+        );
+        assert!(report
+            .to_text()
+            .contains("   5. KTJJT FourOfAKind (wildcards as T): bid 220 x 5 = 1100"));
+
+        let csv = report.to_csv();
+        assert_eq!(csv.lines().count(), 6);
+        assert!(csv.contains("\n1,32T3K,765,765,OnePair,\n"));
+        assert!(csv.ends_with("5,KTJJT,220,1100,FourOfAKind,T"));
+    }
+
+    #[test]
+    fn test_substitution() {
+        let jokers = Rules::jokers();
+
+        assert_eq!(jokers.substitution(&['J'; 5]), Some('A'));
+        assert_eq!(jokers.substitution(&['2', '2', 'J', '3', '3']), Some('3'));
+        assert_eq!(jokers.substitution(&['2', '2', 'K', '3', '4']), None);
+        assert_eq!(Rules::standard().substitution(&['J'; 5]), None);
+    }
+
+    #[test]
+    fn test_explain() {
+        let rules = Rules::standard();
+
+        let explanation = explain(&Hand::parse("KTJJT", &rules), &Hand::parse("KK677", &rules));
+        assert_eq!(explanation.ordering, Ordering::Less);
+        assert_eq!(
+            explanation.to_string(),
+            "KK677 beats KTJJT: same type, card 2 is K against T"
+        );
+
+        assert_eq!(
+            explain(&Hand::parse("T55J5", &rules), &Hand::parse("32T3K", &rules)).to_string(),
+            "T55J5 beats 32T3K: ThreeOfAKind beats OnePair"
+        );
+        assert_eq!(
+            explain(&Hand::parse("32T3K", &rules), &Hand::parse("32T3K", &rules)).reason,
+            Reason::Identical
+        );
+    }
+
+    #[test]
+    fn test_explain_different_lengths() {
+        let rules = Rules::standard();
+        let (long, short) = (Hand::parse("AK23", &rules), Hand::parse("AK2", &rules));
+
+        let explanation = explain(&long, &short);
+        assert_eq!(explanation.ordering, long.cmp(&short));
+        assert_eq!(
+            explanation.reason,
+            Reason::Length {
+                first: 4,
+                second: 3
+            }
+        );
+        assert_eq!(
+            explanation.to_string(),
+            "AK2 beats AK23: same type and cards, 3 cards against 4"
+        );
+
+        let explanation = explain(&short, &long);
+        assert_eq!(explanation.ordering, short.cmp(&long));
+        assert_eq!(explanation.ordering, Ordering::Greater);
+
+        // A differing card still decides before the length does
+        assert_eq!(
+            explain(&Hand::parse("AK3", &rules), &Hand::parse("AK23", &rules)).reason,
+            Reason::Card {
+                position: 2,
+                first: '3',
+                second: '2'
+            }
+        );
+    }
+}
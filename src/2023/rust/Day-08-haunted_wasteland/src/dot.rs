@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use crate::{Movement, Network, INIT, TARGET};
+
+/// Colours given to the ghosts in turn
+const GHOST_COLORS: [&str; 8] = [
+    "red",
+    "blue",
+    "darkgreen",
+    "orange",
+    "purple",
+    "brown",
+    "magenta",
+    "cyan",
+];
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl<'a> Network<'a> {
+    /// Edges every ghost follows until its walk starts repeating, with the
+    /// indices of the ghosts that take them
+    fn ghost_edges(&self) -> HashMap<(&'a str, Movement), Vec<usize>> {
+        let mut edges: HashMap<(&str, Movement), Vec<usize>> = HashMap::new();
+
+        for (ghost, start) in self.ghost_starts().into_iter().enumerate() {
+            let cycle = self.ghost_cycle(start);
+            let mut current = start;
+
+            for step in 0..cycle.offset + cycle.period {
+                let movement = self.instructions[step % self.instructions.len()];
+                let ghosts = edges.entry((current, movement)).or_default();
+                if ghosts.last() != Some(&ghost) {
+                    ghosts.push(ghost);
+                }
+                current = self.next(current, movement);
+            }
+        }
+
+        edges
+    }
+
+    /// Graphviz description of the network. Start nodes are green, end nodes
+    /// red, and the edges each ghost walks are drawn in its own colour
+    pub fn to_dot(&self) -> String {
+        let ghost_edges = self.ghost_edges();
+        let mut lines = vec!["digraph network {".to_string()];
+
+        for &name in &self.names {
+            let style = if name.ends_with(INIT) {
+                " [style=filled, fillcolor=palegreen]"
+            } else if name.ends_with(TARGET) {
+                " [style=filled, fillcolor=salmon]"
+            } else {
+                ""
+            };
+            lines.push(format!("  {}{};", quote(name), style));
+        }
+
+        for &name in &self.names {
+            let node = &self.nodes[name];
+            for (movement, label, target) in [
+                (Movement::Left, "L", node.left),
+                (Movement::Right, "R", node.right),
+            ] {
+                let color = ghost_edges
+                    .get(&(name, movement))
+                    .map(|ghosts| {
+                        let colors = ghosts
+                            .iter()
+                            .map(|&g| GHOST_COLORS[g % GHOST_COLORS.len()])
+                            .collect::<Vec<&str>>();
+                        format!(", color=\"{}\"", colors.join(":"))
+                    })
+                    .unwrap_or_default();
+
+                lines.push(format!(
+                    "  {} -> {} [label={}{}];",
+                    quote(name),
+                    quote(target),
+                    label,
+                    color
+                ));
+            }
+        }
+
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utility_2022::{get_input, is_demo_mode};
+
+    use crate::parse_network;
+
+    #[test]
+    fn test_to_dot() {
+        if !is_demo_mode() {
+            return;
+        }
+
+        let dot = parse_network(&get_input()).unwrap().to_dot();
+
+        assert!(dot.starts_with("digraph network {\n"));
+        assert!(dot.ends_with("\n}"));
+        assert!(dot.contains("  \"11A\" [style=filled, fillcolor=palegreen];"));
+        assert!(dot.contains("  \"22Z\" [style=filled, fillcolor=salmon];"));
+        assert!(dot.contains("  \"XXX\";"));
+        // Ghost 1 goes left out of 11A, ghost 2 right out of 22B
+        assert!(dot.contains("  \"11A\" -> \"11B\" [label=L, color=\"red\"];"));
+        assert!(dot.contains("  \"22B\" -> \"22C\" [label=R, color=\"blue\"];"));
+        assert!(dot.contains("  \"XXX\" -> \"XXX\" [label=L];"));
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use num::integer::Integer;
+
+use crate::{Network, TARGET};
+
+/// Most residue combinations tried with the CRT before simulating instead
+const COMBINATION_LIMIT: usize = 4096;
+/// Most steps simulated before giving up on the ghosts ever meeting
+const SIMULATION_LIMIT: usize = 100_000_000;
+
+/// Where a ghost's walk ends up looping. The state is the node and the position
+/// in the instructions, so after `offset` steps the walk repeats every
+/// `period` steps
+#[derive(Debug, PartialEq)]
+pub struct GhostCycle {
+    pub offset: usize,
+    pub period: usize,
+    /// Steps before `offset + period` where the ghost stands on an end node
+    pub hits: Vec<usize>,
+}
+
+impl GhostCycle {
+    pub fn is_at_end(&self, step: usize) -> bool {
+        let step = if step < self.offset {
+            step
+        } else {
+            self.offset + (step - self.offset) % self.period
+        };
+        self.hits.binary_search(&step).is_ok()
+    }
+
+    /// Every step `s >= offset` with `s % period` in this list is a hit
+    fn residues(&self) -> Vec<usize> {
+        self.hits
+            .iter()
+            .filter(|&&hit| hit >= self.offset)
+            .map(|hit| hit % self.period)
+            .collect()
+    }
+}
+
+impl<'a> Network<'a> {
+    /// Walks from `start` until a (node, instruction index) state repeats
+    pub fn ghost_cycle(&self, start: &'a str) -> GhostCycle {
+        let mut seen: HashMap<(&str, usize), usize> = HashMap::new();
+        let mut hits = vec![];
+        let mut current = start;
+        let len = self.instructions.len();
+
+        for step in 0.. {
+            if let Some(&first) = seen.get(&(current, step % len)) {
+                return GhostCycle {
+                    offset: first,
+                    period: step - first,
+                    hits,
+                };
+            }
+            seen.insert((current, step % len), step);
+
+            if current.ends_with(TARGET) {
+                hits.push(step);
+            }
+            current = self.next(current, self.instructions[step % len]);
+        }
+
+        unreachable!("The walk always repeats")
+    }
+
+    /// First step, at least 1, where every ghost stands on an end node at once.
+    /// Uses the CRT on the cycle of every ghost, and simulates the cycles if
+    /// they hit end nodes too many times to try every combination. `None` if
+    /// they never meet, or the simulation gives up after [`SIMULATION_LIMIT`]
+    /// steps
+    pub fn ghost_steps(&self) -> Option<usize> {
+        let cycles = self
+            .ghost_starts()
+            .into_iter()
+            .map(|start| self.ghost_cycle(start))
+            .collect::<Vec<GhostCycle>>();
+        if cycles.is_empty() {
+            return None;
+        }
+
+        // Until every ghost is inside its cycle, just check each step
+        let settled = cycles.iter().map(|c| c.offset).max().unwrap_or(0).max(1);
+        if let Some(step) = (1..settled).find(|&step| cycles.iter().all(|c| c.is_at_end(step))) {
+            return Some(step);
+        }
+
+        match combine_cycles(&cycles) {
+            Some(solutions) => solutions
+                .into_iter()
+                .map(|(residue, modulus)| first_at_least(residue, modulus, settled as i128))
+                .min()
+                .map(|step| step as usize),
+            None => simulate(&cycles, settled, SIMULATION_LIMIT),
+        }
+    }
+}
+
+/// Solves `x ≡ a1 (mod m1)` and `x ≡ a2 (mod m2)` for moduli that need not be
+/// coprime, returning `x mod lcm(m1, m2)`
+fn crt(a1: i128, m1: i128, a2: i128, m2: i128) -> Option<(i128, i128)> {
+    let egcd = m1.extended_gcd(&m2);
+    let diff = a2 - a1;
+    if diff % egcd.gcd != 0 {
+        return None;
+    }
+
+    let modulus = m1 / egcd.gcd * m2;
+    let k = (diff / egcd.gcd * egcd.x).rem_euclid(m2 / egcd.gcd);
+    Some(((a1 + m1 * k).rem_euclid(modulus), modulus))
+}
+
+/// Every (residue, modulus) that puts all ghosts on end nodes once they are
+/// in their cycles, or `None` if there are too many combinations to try
+fn combine_cycles(cycles: &[GhostCycle]) -> Option<Vec<(i128, i128)>> {
+    let mut solutions = vec![(0, 1)];
+
+    for cycle in cycles {
+        let period = cycle.period as i128;
+        let mut next = vec![];
+
+        for &(residue, modulus) in &solutions {
+            for hit in cycle.residues() {
+                if let Some(solution) = crt(residue, modulus, hit as i128, period) {
+                    next.push(solution);
+                }
+            }
+        }
+
+        next.sort();
+        next.dedup();
+        if next.len() > COMBINATION_LIMIT {
+            return None;
+        }
+        solutions = next;
+    }
+
+    Some(solutions)
+}
+
+/// Smallest `x >= lower` with `x ≡ residue (mod modulus)`
+fn first_at_least(residue: i128, modulus: i128, lower: i128) -> i128 {
+    if residue >= lower {
+        residue
+    } else {
+        residue + Integer::div_ceil(&(lower - residue), &modulus) * modulus
+    }
+}
+
+/// Checks every step from `settled` until all the cycles line up again, but
+/// at most `limit` steps. `None` if no step within them works
+fn simulate(cycles: &[GhostCycle], settled: usize, limit: usize) -> Option<usize> {
+    // Past the limit the exact period does not matter, so stop before overflowing
+    let period = cycles.iter().try_fold(1usize, |period, c| {
+        (period / period.gcd(&c.period))
+            .checked_mul(c.period)
+            .filter(|&p| p <= limit)
+    });
+    let steps = period.unwrap_or(limit);
+
+    (settled..settled + steps).find(|&step| cycles.iter().all(|c| c.is_at_end(step)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_network;
+
+    use super::*;
+
+    // Ghost 1 passes an end node once before looping through 1X2 -> 1CZ -> 1X3,
+    // ghost 2 loops over 2B1 -> 2BZ. The first hits are at 1 and 2, but both
+    // only meet at step 4
+    const OFFSET_NETWORK: &str = "L
+
+1SA = (1PZ, 1PZ)
+1PZ = (1X1, 1X1)
+1X1 = (1X2, 1X2)
+1X2 = (1CZ, 1CZ)
+1CZ = (1X3, 1X3)
+1X3 = (1X2, 1X2)
+2SA = (2B1, 2B1)
+2B1 = (2BZ, 2BZ)
+2BZ = (2B1, 2B1)
+";
+
+    fn brute_force(network: &Network, max_steps: usize) -> Option<usize> {
+        let mut ghosts = network.ghost_starts();
+        for step in 1..=max_steps {
+            let movement = network.instructions[(step - 1) % network.instructions.len()];
+            ghosts = ghosts.iter().map(|g| network.next(g, movement)).collect();
+            if ghosts.iter().all(|g| g.ends_with(TARGET)) {
+                return Some(step);
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_ghost_cycle() {
+        let network = parse_network(OFFSET_NETWORK).unwrap();
+
+        assert_eq!(
+            network.ghost_cycle("1SA"),
+            GhostCycle {
+                offset: 3,
+                period: 3,
+                hits: vec![1, 4],
+            }
+        );
+        assert_eq!(
+            network.ghost_cycle("2SA"),
+            GhostCycle {
+                offset: 1,
+                period: 2,
+                hits: vec![2],
+            }
+        );
+    }
+
+    #[test]
+    fn test_offsets_and_several_hits() {
+        let network = parse_network(OFFSET_NETWORK).unwrap();
+
+        assert_eq!(network.ghost_steps(), Some(4));
+        assert_eq!(brute_force(&network, 100), Some(4));
+
+        let cycles = ["1SA", "2SA"].map(|start| network.ghost_cycle(start));
+        assert_eq!(simulate(&cycles, 3, 100), Some(4));
+    }
+
+    #[test]
+    fn test_simulation_limit() {
+        let cycles = [(3, 2), (5, 4)].map(|(period, hit)| GhostCycle {
+            offset: 0,
+            period,
+            hits: vec![hit],
+        });
+
+        assert_eq!(simulate(&cycles, 1, 100), Some(14));
+        assert_eq!(simulate(&cycles, 1, 10), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(2, 3, 3, 5), Some((8, 15)));
+        assert_eq!(crt(1, 4, 3, 6), Some((9, 12)));
+        assert_eq!(crt(0, 4, 1, 6), None);
+    }
+}
@@ -1,8 +1,11 @@
 #![allow(dead_code, non_snake_case)]
 
-use num::integer::lcm;
-use std::collections::HashMap;
+mod dot;
+mod ghosts;
 
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Movement {
     Left = 0,
     Right = 1,
@@ -17,7 +20,64 @@ struct Node<'a> {
 const TARGET: &str = "Z";
 const INIT: &str = "A";
 
-fn count_steps(input: &str) -> usize {
+/// The instructions and the map of the desert
+#[derive(Debug)]
+struct Network<'a> {
+    instructions: Vec<Movement>,
+    nodes: HashMap<&'a str, Node<'a>>,
+    /// Node names in the order they appear in the input
+    names: Vec<&'a str>,
+}
+
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    /// The first line is empty
+    MissingInstructions,
+    UnknownMovement {
+        movement: char,
+    },
+    /// The line is not like `AAA = (BBB, CCC)`
+    Malformed {
+        line: usize,
+    },
+    DuplicateNode {
+        line: usize,
+        name: String,
+    },
+    /// Every left or right target that is never defined
+    UndefinedReferences(Vec<UndefinedReference>),
+}
+
+#[derive(Debug, PartialEq)]
+struct UndefinedReference {
+    line: usize,
+    node: String,
+    target: String,
+}
+
+/// A node name: anything non-empty without spaces or the separators
+fn parse_name(name: &str) -> Option<&str> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && !name
+            .chars()
+            .any(|c| c.is_whitespace() || "=(),".contains(c));
+    valid.then_some(name)
+}
+
+/// Parses `AAA = (BBB, CCC)` into the name and its left and right targets
+fn parse_node(line: &str) -> Option<(&str, &str, &str)> {
+    let (name, targets) = line.split_once('=')?;
+    let (left, right) = targets
+        .trim()
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .split_once(',')?;
+
+    Some((parse_name(name)?, parse_name(left)?, parse_name(right)?))
+}
+
+fn parse_network(input: &str) -> Result<Network<'_>, ParseError> {
     //
     // E.g.
     // LLR
@@ -25,61 +85,135 @@ fn count_steps(input: &str) -> usize {
     // AAA = (BBB, BBB)
     // BBB = (AAA, ZZZ)
     // ZZZ = (ZZZ, ZZZ)
-    let mut splits = input.split("\n").filter(|s| !s.is_empty());
-    let mut node_map = HashMap::new();
-    let mut current_nodes = Vec::new();
-
-    let instructions = splits.next().unwrap();
-    splits.for_each(|s| {
-        let input = &s[0..=2];
-        let left = &s[7..=9];
-        let right = &s[12..=14];
+    let mut lines = input.lines().enumerate();
+    let mut nodes = HashMap::new();
+    let mut names = Vec::new();
+    let mut lines_by_name = HashMap::new();
+
+    let instructions = lines
+        .next()
+        .map(|(_, line)| line.trim())
+        .filter(|line| !line.is_empty())
+        .ok_or(ParseError::MissingInstructions)?
+        .chars()
+        .map(|c| match c {
+            'L' => Ok(Movement::Left),
+            'R' => Ok(Movement::Right),
+            movement => Err(ParseError::UnknownMovement { movement }),
+        })
+        .collect::<Result<Vec<Movement>, ParseError>>()?;
 
-        let n = Node { left, right };
-        node_map.insert(input, n);
+    for (idx, s) in lines.filter(|(_, s)| !s.trim().is_empty()) {
+        let line = idx + 1;
+        let (input, left, right) = parse_node(s).ok_or(ParseError::Malformed { line })?;
 
-        if input.ends_with(INIT) {
-            current_nodes.push(input);
+        if nodes.insert(input, Node { left, right }).is_some() {
+            return Err(ParseError::DuplicateNode {
+                line,
+                name: input.to_string(),
+            });
         }
-    });
+        names.push(input);
+        lines_by_name.insert(input, line);
+    }
 
-    let cycle = instructions
-        .chars()
-        .map(|c| match c {
-            'L' => Movement::Left,
-            'R' => Movement::Right,
-            _ => panic!("Unknown movement"),
+    let undefined = names
+        .iter()
+        .flat_map(|&name| {
+            let node = &nodes[name];
+            let line = lines_by_name[name];
+            [node.left, node.right]
+                .into_iter()
+                .filter(|target| !nodes.contains_key(target))
+                .map(move |target| UndefinedReference {
+                    line,
+                    node: name.to_string(),
+                    target: target.to_string(),
+                })
         })
-        .cycle();
+        .collect::<Vec<UndefinedReference>>();
+    if !undefined.is_empty() {
+        return Err(ParseError::UndefinedReferences(undefined));
+    }
 
-    let mut cycle_numbers = Vec::with_capacity(instructions.len());
+    Ok(Network {
+        instructions,
+        nodes,
+        names,
+    })
+}
 
-    for current_node in current_nodes {
-        let mut cycle_number = 0;
-        let mut current_node = current_node;
-        for movement in cycle.clone() {
-            cycle_number += 1;
+impl<'a> Network<'a> {
+    fn next(&self, node: &'a str, movement: Movement) -> &'a str {
+        let node = self.nodes.get(node).unwrap();
+        match movement {
+            Movement::Left => node.left,
+            Movement::Right => node.right,
+        }
+    }
 
-            let node = node_map.get(current_node).unwrap();
+    /// Number of steps from `start` until a node matching `is_end`, or `None`
+    /// if the walk loops forever without finding one
+    fn steps(&self, start: &'a str, is_end: impl Fn(&str) -> bool) -> Option<usize> {
+        // After visiting every (node, instruction) pair the walk can only repeat
+        let max_steps = self.nodes.len() * self.instructions.len();
+        let mut current = start;
 
-            current_node = match movement {
-                Movement::Left => node.left,
-                Movement::Right => node.right,
-            };
+        for (step, &movement) in self.instructions.iter().cycle().enumerate() {
+            if step > max_steps {
+                return None;
+            }
+            current = self.next(current, movement);
+            if is_end(current) {
+                return Some(step + 1);
+            }
+        }
 
-            if current_node.ends_with(TARGET) {
-                cycle_numbers.push(cycle_number);
-                break;
+        unreachable!("Instructions cycle forever")
+    }
+
+    /// Nodes every ghost starts from
+    fn ghost_starts(&self) -> Vec<&'a str> {
+        self.names
+            .iter()
+            .copied()
+            .filter(|name| name.ends_with(INIT))
+            .collect()
+    }
+
+    /// Nodes no walk can reach, from `AAA` or from any ghost start, in input
+    /// order
+    fn unreachable_nodes(&self) -> Vec<&'a str> {
+        let mut reached = HashSet::new();
+        let mut queue = self.ghost_starts();
+
+        while let Some(name) = queue.pop() {
+            if reached.insert(name) {
+                let node = &self.nodes[name];
+                queue.extend([node.left, node.right]);
             }
         }
+
+        self.names
+            .iter()
+            .copied()
+            .filter(|name| !reached.contains(name))
+            .collect()
     }
+}
 
-    let count = cycle_numbers
-        .into_iter()
-        .reduce(|acc, next| lcm(acc, next))
-        .unwrap();
+fn part_1(input: &str) -> usize {
+    let network = parse_network(input).expect("Invalid network");
+    network
+        .steps("AAA", |node| node == "ZZZ")
+        .expect("ZZZ cannot be reached from AAA")
+}
 
-    count
+fn part_2(input: &str) -> usize {
+    let network = parse_network(input).expect("Invalid network");
+    network
+        .ghost_steps()
+        .expect("The ghosts never all stand on an end node together")
 }
 
 #[cfg(test)]
@@ -87,34 +221,83 @@ mod tests {
     use super::*;
     use utility_2022::{get_input, is_demo_mode};
 
-    // pub mod part1 {
+    #[test]
+    fn test_parse_any_name_length() {
+        let network = parse_network(
+            "RL
+
+START_A = (MIDDLE, START_A)
+MIDDLE = (Z, MIDDLE)
+Z = (Z, Z)
+ORPHAN = (Z, Z)
+",
+        )
+        .unwrap();
+
+        assert_eq!(network.next("START_A", Movement::Left), "MIDDLE");
+        assert_eq!(network.steps("START_A", |node| node == "Z"), Some(4));
+        assert_eq!(network.unreachable_nodes(), vec!["ORPHAN"]);
+    }
 
-    //     use super::*;
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_network("LR\n\nAAA = (BBB, CCC)\nBBB = (AAA, AAA)\n").unwrap_err(),
+            ParseError::UndefinedReferences(vec![UndefinedReference {
+                line: 3,
+                node: "AAA".to_string(),
+                target: "CCC".to_string(),
+            }])
+        );
+        assert_eq!(
+            parse_network("LR\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)\n").unwrap_err(),
+            ParseError::DuplicateNode {
+                line: 4,
+                name: "AAA".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_network("LR\n\nAAA = AAA, AAA\n").unwrap_err(),
+            ParseError::Malformed { line: 3 }
+        );
+        assert_eq!(
+            parse_network("LXR\n").unwrap_err(),
+            ParseError::UnknownMovement { movement: 'X' }
+        );
+        assert_eq!(
+            parse_network("\nAAA = (AAA, AAA)\n").unwrap_err(),
+            ParseError::MissingInstructions
+        );
+    }
 
-    //     #[test]
-    //     fn test_demo_input() {
-    //         if !is_demo_mode() {
-    //             return;
-    //         }
+    pub mod part1 {
 
-    //         let input = get_input();
-    //         let count = count_steps(&input);
+        use super::*;
 
-    //         assert_eq!(count, 6);
-    //     }
+        #[test]
+        fn test_demo_input() {
+            // The demo input is the ghost example, which has no AAA node
+            let input = "LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)
+";
+            assert_eq!(part_1(input), 6);
+        }
 
-    //     #[test]
-    //     fn test_input() {
-    //         if is_demo_mode() {
-    //             return;
-    //         }
+        #[test]
+        fn test_input() {
+            if is_demo_mode() {
+                return;
+            }
 
-    //         let input = get_input();
-    //         let count = count_steps(&input);
+            let input = get_input();
+            let count = part_1(&input);
 
-    //         println!("Answer pt1: {}", count);
-    //     }
-    // }
+            println!("Answer pt1: {}", count);
+        }
+    }
 
     pub mod part2 {
 
@@ -127,7 +310,7 @@ mod tests {
             }
 
             let input = get_input();
-            let count = count_steps(&input);
+            let count = part_2(&input);
 
             assert_eq!(count, 6);
         }
@@ -139,7 +322,7 @@ mod tests {
             }
 
             let input = get_input();
-            let count = count_steps(&input);
+            let count = part_2(&input);
 
             println!("Answer pt2: {}", count);
         }